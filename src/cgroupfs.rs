@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::mountinfo::{self, Mount};
+
+/// Where the cgroup filesystems are mounted. Most systems use /sys/fs/cgroup,
+/// but chroots and older distros mount v1 controllers under /cgroup, and
+/// hybrid systems put cgroup2 at /sys/fs/cgroup/unified.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CgroupMounts {
+    /// The cgroup2 mount point
+    pub unified: Option<String>,
+    /// v1 mount point per controller; co-mounted controllers ("cpu,cpuacct")
+    /// share one, and named hierarchies are keyed "name=systemd"
+    pub controllers: BTreeMap<String, String>,
+    /// Controllers the cgroup2 root offers (`cgroup.controllers`); on hybrid
+    /// systems only those not claimed by a v1 mount
+    pub v2_controllers: BTreeSet<String>,
+    /// This process's path in each hierarchy, from `/proc/self/cgroup`
+    pub process_paths: BTreeMap<String, String>,
+    /// The path reported as the current cgroup (`parse_current_cgroup_path`)
+    pub process_path: Option<String>,
+    /// Our cgroup as resolved for reporting and the per-controller paths
+    /// rebased to match; only set when reporting on our own cgroup
+    own_cgroup: OnceLock<(String, BTreeMap<String, String>)>,
+}
+
+/// v1 super options that are mount flags rather than controllers.
+const V1_MOUNT_FLAGS: &[&str] = &["rw", "ro", "noprefix", "clone_children", "xattr", "cpuset_v2_mode", "none"];
+
+impl CgroupMounts {
+    pub fn from_mounts(mounts: &[Mount]) -> Self {
+        let mut discovered = CgroupMounts::default();
+        for mount in mounts {
+            match mount.fstype.as_str() {
+                "cgroup2" => discovered.unified = Some(mount.mount_point.clone()),
+                "cgroup" => {
+                    for option in mount.super_options.split(',') {
+                        if V1_MOUNT_FLAGS.contains(&option) || (option.contains('=') && !option.starts_with("name=")) {
+                            continue;
+                        }
+                        discovered.controllers.insert(option.to_string(), mount.mount_point.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        discovered
+    }
+
+    /// Guesses the usual /sys/fs/cgroup layout from what exists there; used
+    /// when mountinfo cannot be read.
+    fn probe_standard_layout() -> Self {
+        let root = "/sys/fs/cgroup";
+        CgroupMounts {
+            unified: Path::new(root).join("cgroup.controllers").exists().then(|| root.to_string()),
+            controllers: ["cpu", "cpuacct", "cpuset", "memory", "hugetlb", "pids", "blkio"]
+                .iter()
+                .map(|controller| (controller.to_string(), format!("{}/{}", root, controller)))
+                .filter(|(_, dir)| Path::new(dir).is_dir())
+                .collect(),
+            ..CgroupMounts::default()
+        }
+    }
+
+    /// Records the controllers listed in the cgroup2 root's `cgroup.controllers`.
+    pub fn with_v2_controllers(mut self, cgroup_controllers: &str) -> Self {
+        self.v2_controllers = cgroup_controllers.split_whitespace().map(str::to_string).collect();
+        self
+    }
+
+    /// Records where this process sits in each hierarchy.
+    pub fn with_process_cgroups(mut self, proc_self_cgroup: &str) -> Self {
+        self.process_paths = parse_controller_paths(proc_self_cgroup);
+        self.process_path = Some(parse_current_cgroup_path(proc_self_cgroup));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unified.is_none() && self.controllers.is_empty()
+    }
+
+    fn has_v1_controllers(&self) -> bool {
+        self.controllers.keys().any(|controller| !controller.starts_with("name="))
+    }
+
+    /// "hybrid" when v1 controllers are mounted next to a cgroup2 mount
+    /// (`systemd.unified_cgroup_hierarchy=0`), else "v1" or "v2".
+    pub fn version(&self) -> Option<&'static str> {
+        match (self.has_v1_controllers(), self.unified.is_some()) {
+            (true, true) => Some("hybrid"),
+            (true, false) => Some("v1"),
+            (false, true) => Some("v2"),
+            (false, false) => None,
+        }
+    }
+
+    /// Which hierarchy handles `controller`: "v1" when a v1 mount carries it,
+    /// "v2" when the cgroup2 root lists it (or on a pure v2 system whose list
+    /// could not be read), None when neither does.
+    pub fn hierarchy(&self, controller: &str) -> Option<&'static str> {
+        if self.controllers.contains_key(controller) {
+            Some("v1")
+        } else if self.v2_controllers.contains(controller) || self.version() == Some("v2") {
+            Some("v2")
+        } else {
+            None
+        }
+    }
+
+    /// Every controller and the hierarchy handling it.
+    pub fn controller_versions(&self) -> BTreeMap<String, String> {
+        self.v2_controllers
+            .iter()
+            .map(|controller| (controller.clone(), "v2".to_string()))
+            .chain(
+                self.controllers
+                    .keys()
+                    .filter(|controller| !controller.starts_with("name="))
+                    .map(|controller| (controller.clone(), "v1".to_string())),
+            )
+            .collect()
+    }
+
+    pub fn v1_root(&self, controller: &str) -> Option<&str> {
+        self.controllers.get(controller).map(String::as_str)
+    }
+
+    /// `cgroup_path` under the cgroup2 mount.
+    pub fn v2_dir(&self, cgroup_path: &str) -> Option<String> {
+        self.unified.as_deref().map(|root| format!("{}{}", root, cgroup_path.trim_end_matches('/')))
+    }
+
+    /// `file` in `cgroup_path` under the cgroup2 mount.
+    pub fn v2_file(&self, cgroup_path: &str, file: &str) -> Option<String> {
+        self.unified.as_deref().map(|root| join(root, cgroup_path, file))
+    }
+
+    /// Marks `resolved` as our own cgroup: what `process_path` resolved to
+    /// on these mounts. Whatever host prefix resolution stripped from it is
+    /// stripped from each per-controller path too. Only the first call counts.
+    pub fn set_own_cgroup(&self, resolved: &str) {
+        let prefix = match self.process_path.as_deref() {
+            Some(raw) if resolved == "/" => raw.trim_end_matches('/'),
+            Some(raw) => raw.strip_suffix(resolved).unwrap_or(""),
+            None => "",
+        };
+        let paths = self
+            .process_paths
+            .iter()
+            .map(|(controller, path)| {
+                let rebased = match path.strip_prefix(prefix) {
+                    Some("") => "/",
+                    Some(rest) if rest.starts_with('/') => rest,
+                    _ => path,
+                };
+                (controller.clone(), rebased.to_string())
+            })
+            .collect();
+        let _ = self.own_cgroup.set((resolved.to_string(), paths));
+    }
+
+    /// `cgroup_path` in the v1 hierarchy holding `controller`. A process can
+    /// sit at a different path in each v1 hierarchy (older container
+    /// runtimes, manual cgcreate setups), so our own cgroup (see
+    /// `set_own_cgroup`) maps to the path this process actually has under
+    /// `controller`; any other path, including an explicit `--cgroup-path`,
+    /// is used as given.
+    pub fn v1_path<'a>(&'a self, controller: &str, cgroup_path: &'a str) -> &'a str {
+        match self.own_cgroup.get() {
+            Some((own, paths)) if own == cgroup_path => paths.get(controller).map_or(cgroup_path, String::as_str),
+            _ => cgroup_path,
+        }
+    }
+
+    /// `cgroup_path` under the v1 hierarchy holding `controller`.
+    pub fn v1_dir(&self, controller: &str, cgroup_path: &str) -> Option<String> {
+        self.v1_root(controller)
+            .map(|root| format!("{}{}", root, self.v1_path(controller, cgroup_path).trim_end_matches('/')))
+    }
+
+    /// `file` in `cgroup_path` under the v1 hierarchy holding `controller`.
+    pub fn v1_file(&self, controller: &str, cgroup_path: &str, file: &str) -> Option<String> {
+        self.v1_root(controller).map(|root| join(root, self.v1_path(controller, cgroup_path), file))
+    }
+
+    /// `file` in the root cgroup of the v1 hierarchy holding `controller`,
+    /// never mapped to this process's own path.
+    pub fn v1_root_file(&self, controller: &str, file: &str) -> Option<String> {
+        self.v1_root(controller).map(|root| join(root, "/", file))
+    }
+}
+
+fn join(root: &str, cgroup_path: &str, file: &str) -> String {
+    format!("{}{}/{}", root, cgroup_path.trim_end_matches('/'), file)
+}
+
+static MOUNTS: OnceLock<CgroupMounts> = OnceLock::new();
+
+/// The cgroup mounts of this process, read from `/proc/self/mountinfo`, the
+/// cgroup2 root's `cgroup.controllers` and `/proc/self/cgroup` on first use.
+pub fn mounts() -> &'static CgroupMounts {
+    MOUNTS.get_or_init(|| {
+        let mut mounts = discover().unwrap_or_else(CgroupMounts::probe_standard_layout);
+        if let Some(list) = mounts.v2_file("/", "cgroup.controllers").and_then(|path| std::fs::read_to_string(path).ok()) {
+            mounts = mounts.with_v2_controllers(&list);
+        }
+        match std::fs::read_to_string("/proc/self/cgroup") {
+            Ok(contents) => mounts.with_process_cgroups(&contents),
+            Err(_) => mounts,
+        }
+    })
+}
+
+/// None when mountinfo cannot be read; empty when it lists no cgroup mounts.
+pub fn discover() -> Option<CgroupMounts> {
+    std::fs::read_to_string("/proc/self/mountinfo")
+        .ok()
+        .map(|contents| CgroupMounts::from_mounts(&mountinfo::parse_mountinfo(&contents)))
+}
+
+/// Splits `/proc/self/cgroup` into one entry per controller, sorted so output
+/// diffs cleanly between runs. `4:cpu,cpuacct:/a` yields both "cpu" and
+/// "cpuacct"; the v2 `0::/path` line is keyed "unified".
+pub fn parse_controller_paths(contents: &str) -> BTreeMap<String, String> {
+    let mut paths = BTreeMap::new();
+    for line in contents.lines() {
+        let mut parts = line.splitn(3, ':');
+        let (Some(_id), Some(controllers), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        if controllers.is_empty() {
+            paths.insert("unified".to_string(), path.to_string());
+        } else {
+            for controller in controllers.split(',') {
+                paths.insert(controller.to_string(), path.to_string());
+            }
+        }
+    }
+    paths
+}
+
+/// Picks the cgroup path from `/proc/self/cgroup` contents: the v2 `0::`
+/// entry, else the v1 hierarchy whose controller list includes `memory`
+/// (e.g. `5:memory,hugetlb:/path`), else the first real v1 controller.
+pub fn parse_current_cgroup_path(contents: &str) -> String {
+    // For cgroup v2, the format is: 0::/path
+    for line in contents.lines() {
+        if let Some(path) = line.strip_prefix("0::") {
+            return path.to_string();
+        }
+    }
+
+    // For cgroup v1, the format is: id:controller[,controller...]:/path
+    let v1_entries: Vec<(&str, &str)> = contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ':');
+            let _id = parts.next()?;
+            let controllers = parts.next()?;
+            let path = parts.next()?;
+            Some((controllers, path))
+        })
+        .filter(|(controllers, _)| !controllers.is_empty())
+        .collect();
+
+    if let Some((_, path)) = v1_entries
+        .iter()
+        .find(|(controllers, _)| controllers.split(',').any(|c| c == "memory"))
+    {
+        return path.to_string();
+    }
+
+    // No memory controller: prefer a real controller over named hierarchies (name=systemd)
+    v1_entries
+        .iter()
+        .find(|(controllers, _)| !controllers.starts_with("name="))
+        .or_else(|| v1_entries.first())
+        .map(|(_, path)| path.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_mountinfo(contents: &str) -> CgroupMounts {
+        CgroupMounts::from_mounts(&mountinfo::parse_mountinfo(contents))
+    }
+
+    #[test]
+    fn pure_v2_mount() {
+        let mounts = from_mountinfo("30 25 0:26 / /sys/fs/cgroup rw,nosuid - cgroup2 cgroup2 rw,nsdelegate\n");
+        assert_eq!(mounts.version(), Some("v2"));
+        assert_eq!(mounts.v2_file("/user.slice", "memory.max").as_deref(), Some("/sys/fs/cgroup/user.slice/memory.max"));
+        assert_eq!(mounts.v2_file("/", "cpu.max").as_deref(), Some("/sys/fs/cgroup/cpu.max"));
+        assert_eq!(mounts.v1_file("memory", "/", "memory.limit_in_bytes"), None);
+    }
+
+    #[test]
+    fn pure_v1_layout() {
+        let mounts = from_mountinfo(
+            "\
+24 22 0:20 / /sys/fs/cgroup ro,nosuid - tmpfs tmpfs ro,mode=755
+25 24 0:21 / /sys/fs/cgroup/systemd rw,nosuid - cgroup cgroup rw,xattr,release_agent=/lib/systemd/systemd-cgroups-agent,name=systemd
+26 24 0:22 / /sys/fs/cgroup/cpu,cpuacct rw,nosuid - cgroup cgroup rw,cpu,cpuacct
+27 24 0:23 / /sys/fs/cgroup/memory rw,nosuid - cgroup cgroup rw,memory
+",
+        );
+        assert_eq!(mounts.version(), Some("v1"));
+        assert_eq!(mounts.unified, None);
+        assert_eq!(mounts.v1_root("cpu"), Some("/sys/fs/cgroup/cpu,cpuacct"));
+        assert_eq!(mounts.v1_root("cpuacct"), Some("/sys/fs/cgroup/cpu,cpuacct"));
+        assert_eq!(mounts.v1_root("name=systemd"), Some("/sys/fs/cgroup/systemd"));
+        assert_eq!(mounts.controllers.len(), 4);
+    }
+
+    #[test]
+    fn hybrid_with_unified() {
+        let mounts = from_mountinfo(
+            "\
+24 22 0:20 / /sys/fs/cgroup ro,nosuid - tmpfs tmpfs ro,mode=755
+25 24 0:21 / /sys/fs/cgroup/unified rw,nosuid - cgroup2 cgroup2 rw,nsdelegate
+27 24 0:23 / /sys/fs/cgroup/memory rw,nosuid - cgroup cgroup rw,memory
+",
+        );
+        assert_eq!(mounts.version(), Some("hybrid"));
+        assert_eq!(mounts.v2_file("/a", "cgroup.procs").as_deref(), Some("/sys/fs/cgroup/unified/a/cgroup.procs"));
+        assert_eq!(mounts.v1_file("memory", "/a", "memory.limit_in_bytes").as_deref(), Some("/sys/fs/cgroup/memory/a/memory.limit_in_bytes"));
+
+        // cpu and pids moved to the unified hierarchy, memory stayed on v1
+        let mounts = mounts.with_v2_controllers("cpu pids\n");
+        assert_eq!(mounts.hierarchy("memory"), Some("v1"));
+        assert_eq!(mounts.hierarchy("cpu"), Some("v2"));
+        assert_eq!(mounts.hierarchy("cpuset"), None);
+        let versions: Vec<String> = mounts.controller_versions().iter().map(|(name, version)| format!("{}={}", name, version)).collect();
+        assert_eq!(versions, ["cpu=v2", "memory=v1", "pids=v2"]);
+    }
+
+    #[test]
+    fn nonstandard_cgroup_prefix() {
+        let mounts = from_mountinfo(
+            "\
+40 22 0:30 / /cgroup/cpu rw,relatime - cgroup cgroup rw,cpu
+41 22 0:31 / /cgroup/memory rw,relatime - cgroup cgroup rw,memory
+",
+        );
+        assert_eq!(mounts.version(), Some("v1"));
+        assert_eq!(mounts.v1_file("cpu", "/job", "cpu.cfs_quota_us").as_deref(), Some("/cgroup/cpu/job/cpu.cfs_quota_us"));
+        // The tmpfs placeholder alone holds no limits
+        assert!(from_mountinfo("24 22 0:20 / /sys/fs/cgroup ro - tmpfs tmpfs ro\n").is_empty());
+    }
+
+    #[test]
+    fn cgroup_path_matches_comounted_memory_controller() {
+        let contents = "5:cpu,cpuacct:/cpu-path\n4:memory,hugetlb:/mem-path\n1:name=systemd:/sd-path\n";
+        assert_eq!(parse_current_cgroup_path(contents), "/mem-path");
+    }
+
+    #[test]
+    fn controller_paths_are_split_and_sorted() {
+        let contents = "5:memory:/mem-path\n3:cpu,cpuacct:/cpu-path\n1:name=systemd:/sd-path\n0::/unified-path\n";
+        let paths = parse_controller_paths(contents);
+        assert_eq!(
+            paths.iter().map(|(controller, path)| format!("{}={}", controller, path)).collect::<Vec<_>>(),
+            ["cpu=/cpu-path", "cpuacct=/cpu-path", "memory=/mem-path", "name=systemd=/sd-path", "unified=/unified-path"]
+        );
+    }
+
+    #[test]
+    fn cgroup_path_without_memory_uses_first_real_controller() {
+        let contents = "2:name=systemd:/sd-path\n3:cpu,cpuacct:/cpu-path\n1:pids:/pids-path\n";
+        assert_eq!(parse_current_cgroup_path(contents), "/cpu-path");
+        assert_eq!(parse_current_cgroup_path("1:name=systemd:/sd-path\n"), "/sd-path");
+    }
+
+    #[test]
+    fn own_cgroup_paths_follow_resolution() {
+        let mounts = CgroupMounts::default()
+            .with_process_cgroups("5:cpu,cpuacct:/docker/abc/cpu-grp\n4:memory:/docker/abc/mem-grp\n");
+        assert_eq!(mounts.v1_path("cpu", "/docker/abc/mem-grp"), "/docker/abc/mem-grp");
+
+        // Resolution stripped the host prefix from the memory path
+        mounts.set_own_cgroup("/mem-grp");
+        assert_eq!(mounts.v1_path("cpu", "/mem-grp"), "/cpu-grp");
+        assert_eq!(mounts.v1_path("memory", "/mem-grp"), "/mem-grp");
+        assert_eq!(mounts.v1_path("cpu", "/docker/abc/mem-grp"), "/docker/abc/mem-grp");
+    }
+
+    #[test]
+    fn cgroup_path_prefers_v2_entry() {
+        assert_eq!(parse_current_cgroup_path("0::/user.slice/session-2.scope\n"), "/user.slice/session-2.scope");
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::cpulist::parse_cpu_list;
+
+const SYS_CPU: &str = "/sys/devices/system/cpu";
+
+/// A set of CPUs sharing one core type. Homogeneous machines report a single
+/// "uniform" group.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct CoreGroup {
+    pub count: usize,
+    /// "performance", "efficiency", "mid", or "uniform"
+    pub kind: String,
+    pub max_khz: Option<u64>,
+}
+
+pub fn read_core_groups() -> Vec<CoreGroup> {
+    let cpus = list_cpu_ids(Path::new(SYS_CPU));
+    if cpus.is_empty() {
+        return Vec::new();
+    }
+
+    // x86 hybrid (Alder Lake and later): separate PMU event sources per core type
+    if let (Some(core), Some(atom)) = (
+        read_cpu_list_file("/sys/devices/cpu_core/cpus"),
+        read_cpu_list_file("/sys/devices/cpu_atom/cpus"),
+    ) {
+        return vec![
+            CoreGroup { count: core.len(), kind: "performance".to_string(), max_khz: max_khz_of(&core) },
+            CoreGroup { count: atom.len(), kind: "efficiency".to_string(), max_khz: max_khz_of(&atom) },
+        ];
+    }
+
+    // ARM big.LITTLE / DynamIQ: group by the scheduler's cpu_capacity
+    let mut by_capacity: BTreeMap<u64, Vec<u32>> = BTreeMap::new();
+    for &cpu in &cpus {
+        if let Some(capacity) = read_u64(&format!("{}/cpu{}/cpu_capacity", SYS_CPU, cpu)) {
+            by_capacity.entry(capacity).or_default().push(cpu);
+        }
+    }
+    if by_capacity.len() > 1 {
+        let last = by_capacity.len() - 1;
+        // Highest capacity first
+        return by_capacity
+            .values()
+            .enumerate()
+            .rev()
+            .map(|(idx, group)| {
+                let kind = match idx {
+                    0 => "efficiency",
+                    i if i == last => "performance",
+                    _ => "mid",
+                };
+                CoreGroup { count: group.len(), kind: kind.to_string(), max_khz: max_khz_of(group) }
+            })
+            .collect();
+    }
+
+    vec![CoreGroup { count: cpus.len(), kind: "uniform".to_string(), max_khz: max_khz_of(&cpus) }]
+}
+
+fn list_cpu_ids(sys_cpu: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(sys_cpu) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .strip_prefix("cpu")
+                .and_then(|id| id.parse::<u32>().ok())
+        })
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+fn read_cpu_list_file(path: &str) -> Option<Vec<u32>> {
+    let cpus = parse_cpu_list(&fs::read_to_string(path).ok()?);
+    if cpus.is_empty() { None } else { Some(cpus) }
+}
+
+fn read_u64(path: &str) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+}
+
+fn max_khz_of(cpus: &[u32]) -> Option<u64> {
+    cpus.iter()
+        .filter_map(|cpu| read_u64(&format!("{}/cpu{}/cpufreq/cpuinfo_max_freq", SYS_CPU, cpu)))
+        .max()
+}
@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::cpulist::parse_cpu_list;
+
+const SYS_CPU: &str = "/sys/devices/system/cpu";
+
+/// One distinct cache geometry. Caches shared between CPUs are counted once
+/// per physical instance, not once per CPU that can see them.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct CpuCache {
+    /// None when only the `/proc/cpuinfo` fallback was available
+    pub level: Option<u32>,
+    #[serde(rename = "type")]
+    pub cache_type: String,
+    pub size_bytes: u64,
+    pub shared_by_cpus: Option<usize>,
+    pub instances: usize,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone, Default)]
+pub struct CpuCacheInfo {
+    pub caches: Vec<CpuCache>,
+    /// Sum of all distinct L3 instances across packages
+    pub l3_total_bytes: Option<u64>,
+}
+
+pub fn read_cpu_caches() -> CpuCacheInfo {
+    let info = read_sysfs_caches(Path::new(SYS_CPU));
+    if !info.caches.is_empty() {
+        return info;
+    }
+
+    // Fallback: the per-core "cache size" line (usually the last-level cache)
+    let caches = fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|contents| {
+            contents
+                .lines()
+                .find(|line| line.starts_with("cache size"))
+                .and_then(|line| line.split(':').nth(1))
+                .and_then(|value| parse_cache_size(&value.replace(' ', "")))
+        })
+        .map(|size_bytes| {
+            vec![CpuCache {
+                level: None,
+                cache_type: "Unified".to_string(),
+                size_bytes,
+                shared_by_cpus: None,
+                instances: 1,
+            }]
+        })
+        .unwrap_or_default();
+    CpuCacheInfo { caches, l3_total_bytes: None }
+}
+
+fn read_sysfs_caches(sys_cpu: &Path) -> CpuCacheInfo {
+    // Unique physical instances keyed by (level, type, shared_cpu_list)
+    let mut seen = HashSet::new();
+    let mut instances: Vec<(u32, String, u64, usize)> = Vec::new();
+
+    let Ok(entries) = fs::read_dir(sys_cpu) else {
+        return CpuCacheInfo::default();
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let is_cpu_dir = name
+            .strip_prefix("cpu")
+            .map(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or(false);
+        if !is_cpu_dir {
+            continue;
+        }
+        let Ok(indexes) = fs::read_dir(entry.path().join("cache")) else {
+            continue;
+        };
+        for index in indexes.flatten() {
+            let dir = index.path();
+            let read = |file: &str| fs::read_to_string(dir.join(file)).ok().map(|s| s.trim().to_string());
+            let (Some(level), Some(cache_type), Some(size)) = (
+                read("level").and_then(|l| l.parse::<u32>().ok()),
+                read("type"),
+                read("size").and_then(|s| parse_cache_size(&s)),
+            ) else {
+                continue;
+            };
+            let shared = read("shared_cpu_list").unwrap_or_else(|| name.to_string());
+            if seen.insert((level, cache_type.clone(), shared.clone())) {
+                let shared_by = parse_cpu_list(&shared).len().max(1);
+                instances.push((level, cache_type, size, shared_by));
+            }
+        }
+    }
+
+    let l3_total_bytes = instances
+        .iter()
+        .filter(|(level, ..)| *level == 3)
+        .map(|(_, _, size, _)| *size)
+        .reduce(|a, b| a + b);
+
+    let mut caches: Vec<CpuCache> = Vec::new();
+    for (level, cache_type, size_bytes, shared_by) in instances {
+        match caches.iter_mut().find(|c| {
+            c.level == Some(level)
+                && c.cache_type == cache_type
+                && c.size_bytes == size_bytes
+                && c.shared_by_cpus == Some(shared_by)
+        }) {
+            Some(existing) => existing.instances += 1,
+            None => caches.push(CpuCache {
+                level: Some(level),
+                cache_type,
+                size_bytes,
+                shared_by_cpus: Some(shared_by),
+                instances: 1,
+            }),
+        }
+    }
+    caches.sort_by(|a, b| (a.level, &a.cache_type).cmp(&(b.level, &b.cache_type)));
+
+    CpuCacheInfo { caches, l3_total_bytes }
+}
+
+/// Parses sysfs/cpuinfo cache sizes such as "32K", "1024K", "32M", "33792KB".
+fn parse_cache_size(value: &str) -> Option<u64> {
+    let value = value.trim().trim_end_matches(['B', 'b']);
+    let (digits, multiplier) = match value.chars().last()? {
+        'K' | 'k' => (&value[..value.len() - 1], 1024),
+        'M' | 'm' => (&value[..value.len() - 1], 1024 * 1024),
+        'G' | 'g' => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    digits.trim().parse::<u64>().ok().map(|n| n * multiplier)
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+const SYS_CPU: &str = "/sys/devices/system/cpu";
+
+/// CPU frequency scaling state. All fields are None when cpufreq is not
+/// exposed (common in VMs).
+#[derive(Serialize, JsonSchema, Debug, Clone, Default)]
+pub struct CpuFrequencyInfo {
+    pub governor: Option<String>,
+    /// True when not every CPU uses the same governor as cpu0
+    pub governors_differ: bool,
+    pub scaling_driver: Option<String>,
+    pub turbo_enabled: Option<bool>,
+}
+
+impl CpuFrequencyInfo {
+    /// `powersave` is only a real slowdown with the generic cpufreq drivers;
+    /// intel_pstate and amd-pstate-epp use the name for their dynamic mode.
+    pub fn is_powersave_throttled(&self) -> bool {
+        let dynamic_driver = matches!(
+            self.scaling_driver.as_deref(),
+            Some("intel_pstate") | Some("amd-pstate-epp")
+        );
+        self.governor.as_deref() == Some("powersave") && !dynamic_driver
+    }
+}
+
+pub fn read_cpu_frequency_info() -> CpuFrequencyInfo {
+    let sys_cpu = Path::new(SYS_CPU);
+    let governor = read_trimmed(&sys_cpu.join("cpu0/cpufreq/scaling_governor"));
+    let scaling_driver = read_trimmed(&sys_cpu.join("cpu0/cpufreq/scaling_driver"));
+
+    let mut governors_differ = false;
+    if let Some(cpu0_governor) = &governor
+        && let Ok(entries) = fs::read_dir(sys_cpu)
+    {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let is_cpu_dir = name
+                .strip_prefix("cpu")
+                .map(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+                .unwrap_or(false);
+            if !is_cpu_dir {
+                continue;
+            }
+            if let Some(other) = read_trimmed(&entry.path().join("cpufreq/scaling_governor"))
+                && &other != cpu0_governor
+            {
+                governors_differ = true;
+                break;
+            }
+        }
+    }
+
+    // Generic drivers expose "boost" (1 = on); intel_pstate exposes "no_turbo" (1 = off)
+    let turbo_enabled = match read_trimmed(&sys_cpu.join("cpufreq/boost")) {
+        Some(boost) => Some(boost == "1"),
+        None => read_trimmed(&sys_cpu.join("intel_pstate/no_turbo")).map(|no_turbo| no_turbo == "0"),
+    };
+
+    CpuFrequencyInfo {
+        governor,
+        governors_differ,
+        scaling_driver,
+        turbo_enabled,
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
@@ -0,0 +1,82 @@
+/// Parses a kernel CPU/node list such as "0-3,8-11" or "5" into the
+/// individual ids. Malformed entries are skipped.
+pub fn parse_cpu_list(list: &str) -> Vec<u32> {
+    let mut ids = Vec::new();
+    for part in list.trim().split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<u32>(), end.parse::<u32>())
+                    && start <= end
+                {
+                    ids.extend(start..=end);
+                }
+            }
+            None => {
+                if let Ok(id) = part.parse::<u32>() {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+    ids
+}
+
+/// Formats ids back into the kernel's compact form, e.g. [0, 1, 2, 3, 8] as
+/// "0-3,8". Input is sorted and deduplicated first.
+pub fn format_cpu_list(ids: &[u32]) -> String {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = ids.into_iter().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end += 1;
+            iter.next();
+        }
+        ranges.push(if start == end { start.to_string() } else { format!("{}-{}", start, end) });
+    }
+    ranges.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_range() {
+        assert_eq!(parse_cpu_list("0-63\n").len(), 64);
+        assert_eq!(parse_cpu_list("0-63").last(), Some(&63));
+    }
+
+    #[test]
+    fn parses_single_cpu() {
+        assert_eq!(parse_cpu_list("0"), vec![0]);
+    }
+
+    #[test]
+    fn parses_multiple_ranges() {
+        assert_eq!(parse_cpu_list("0-3,8-11"), vec![0, 1, 2, 3, 8, 9, 10, 11]);
+        assert_eq!(parse_cpu_list("1,3,5-6"), vec![1, 3, 5, 6]);
+    }
+
+    #[test]
+    fn skips_empty_and_malformed_entries() {
+        assert!(parse_cpu_list("").is_empty());
+        assert!(parse_cpu_list("\n").is_empty());
+        assert_eq!(parse_cpu_list("x,2,5-3"), vec![2]);
+    }
+
+    #[test]
+    fn formats_compact_ranges() {
+        assert_eq!(format_cpu_list(&[0, 1, 2, 3, 8]), "0-3,8");
+        assert_eq!(format_cpu_list(&[5, 1, 3, 2]), "1-3,5");
+        assert_eq!(format_cpu_list(&[]), "");
+        assert_eq!(format_cpu_list(&parse_cpu_list("0-3,8-11")), "0-3,8-11");
+    }
+}
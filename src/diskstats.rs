@@ -0,0 +1,130 @@
+use std::fs;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Host-level I/O counters for one block device, from `/proc/diskstats`.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct DiskStat {
+    pub device: String,
+    pub reads_completed: u64,
+    pub sectors_read: u64,
+    pub read_time_ms: u64,
+    pub writes_completed: u64,
+    pub sectors_written: u64,
+    pub write_time_ms: u64,
+    pub io_time_ms: u64,
+}
+
+pub fn read_diskstats() -> Vec<DiskStat> {
+    fs::read_to_string("/proc/diskstats")
+        .map(|contents| parse_diskstats(&contents))
+        .unwrap_or_default()
+}
+
+/// Parses `/proc/diskstats`, keeping whole physical devices only (partitions,
+/// loop and ram devices are skipped).
+pub fn parse_diskstats(contents: &str) -> Vec<DiskStat> {
+    let mut disks = Vec::new();
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // major minor name + at least 11 counters
+        if fields.len() < 14 {
+            continue;
+        }
+        let name = fields[2];
+        if is_virtual_device(name) || is_partition(name) {
+            continue;
+        }
+        let counter = |idx: usize| fields[idx].parse::<u64>().ok();
+        if let (Some(reads), Some(sectors_read), Some(read_ms), Some(writes), Some(sectors_written), Some(write_ms), Some(io_ms)) = (
+            counter(3),
+            counter(5),
+            counter(6),
+            counter(7),
+            counter(9),
+            counter(10),
+            counter(12),
+        ) {
+            disks.push(DiskStat {
+                device: name.to_string(),
+                reads_completed: reads,
+                sectors_read,
+                read_time_ms: read_ms,
+                writes_completed: writes,
+                sectors_written,
+                write_time_ms: write_ms,
+                io_time_ms: io_ms,
+            });
+        }
+    }
+    disks
+}
+
+fn is_virtual_device(name: &str) -> bool {
+    name.starts_with("loop") || name.starts_with("ram")
+}
+
+fn is_partition(name: &str) -> bool {
+    // nvme0n1p1, mmcblk0p1: partition suffix is "p<digits>" after a digit
+    for prefix in ["nvme", "mmcblk"] {
+        if name.starts_with(prefix) {
+            return match name.rfind('p') {
+                Some(idx) if idx > prefix.len() => {
+                    let (head, tail) = name.split_at(idx);
+                    head.ends_with(|c: char| c.is_ascii_digit())
+                        && tail.len() > 1
+                        && tail[1..].chars().all(|c| c.is_ascii_digit())
+                }
+                _ => false,
+            };
+        }
+    }
+    // sda1, vdb2, xvda1, hdc3: letters followed by a partition number
+    for prefix in ["sd", "vd", "xvd", "hd"] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            let letters = rest.trim_end_matches(|c: char| c.is_ascii_digit());
+            return !letters.is_empty()
+                && letters.len() < rest.len()
+                && letters.chars().all(|c| c.is_ascii_lowercase());
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+   7       0 loop0 52 0 2136 11 0 0 0 0 0 28 11 0 0 0 0 0 0
+   1       0 ram0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+   8       0 sda 81734 22153 5925858 31543 220318 192113 9834904 206121 0 193472 241306 0 0 0 0 8519 3641
+   8       1 sda1 81563 22153 5918418 31497 220318 192113 9834904 206121 0 193420 237618 0 0 0 0 0 0
+ 259       0 nvme0n1 4242 10 352814 1521 1234 567 89012 3456 0 2790 4977 0 0 0 0 0 0
+ 259       1 nvme0n1p1 120 0 5210 30 2 0 16 1 0 40 31 0 0 0 0 0 0
+ 253       0 dm-0 1000 0 8000 100 2000 0 16000 200 0 250 300 0 0 0 0 0 0
+";
+
+    #[test]
+    fn parse_diskstats_keeps_whole_devices() {
+        let disks = parse_diskstats(FIXTURE);
+        let names: Vec<&str> = disks.iter().map(|d| d.device.as_str()).collect();
+        assert_eq!(names, vec!["sda", "nvme0n1", "dm-0"]);
+
+        let sda = &disks[0];
+        assert_eq!(sda.reads_completed, 81734);
+        assert_eq!(sda.sectors_read, 5925858);
+        assert_eq!(sda.read_time_ms, 31543);
+        assert_eq!(sda.writes_completed, 220318);
+        assert_eq!(sda.sectors_written, 9834904);
+        assert_eq!(sda.write_time_ms, 206121);
+        assert_eq!(sda.io_time_ms, 193472);
+    }
+
+    #[test]
+    fn parse_diskstats_skips_short_lines() {
+        assert!(parse_diskstats("8 0 sda 1 2 3\n").is_empty());
+        assert!(parse_diskstats("").is_empty());
+    }
+}
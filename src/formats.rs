@@ -0,0 +1,176 @@
+use serde_json::Value;
+
+/// Renders a report as YAML. JSON strings are valid YAML double-quoted
+/// scalars, so strings are emitted with JSON quoting.
+pub fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    write_yaml(value, 0, &mut out);
+    out
+}
+
+fn write_yaml(value: &Value, indent: usize, out: &mut String) {
+    let pad = "  ".repeat(indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                match child {
+                    Value::Object(inner) if !inner.is_empty() => {
+                        out.push_str(&format!("{}{}:\n", pad, key));
+                        write_yaml(child, indent + 1, out);
+                    }
+                    Value::Array(items) if !items.is_empty() => {
+                        out.push_str(&format!("{}{}:\n", pad, key));
+                        write_yaml(child, indent, out);
+                    }
+                    scalar => out.push_str(&format!("{}{}: {}\n", pad, key, yaml_scalar(scalar))),
+                }
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                match item {
+                    Value::Object(inner) if !inner.is_empty() => {
+                        // First key on the dash line, the rest aligned under it
+                        let mut nested = String::new();
+                        write_yaml(item, indent + 1, &mut nested);
+                        let nested = nested.replacen(&format!("{}  ", pad), &format!("{}- ", pad), 1);
+                        out.push_str(&nested);
+                    }
+                    Value::Array(inner) if !inner.is_empty() => {
+                        out.push_str(&format!("{}-\n", pad));
+                        write_yaml(item, indent + 1, out);
+                    }
+                    scalar => out.push_str(&format!("{}- {}\n", pad, yaml_scalar(scalar))),
+                }
+            }
+        }
+        scalar => out.push_str(&format!("{}{}\n", pad, yaml_scalar(scalar))),
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// One `dotted.path=value` line per leaf, e.g. `memory.cgroup_memory_limit_bytes=268435456`.
+/// Array elements are addressed as `disks[0].device`.
+pub fn to_flat(value: &Value) -> String {
+    let mut lines = Vec::new();
+    flatten(value, "", &mut |path, leaf| {
+        let text = match leaf {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        lines.push(format!("{}={}\n", path, text));
+    });
+    lines.concat()
+}
+
+/// Prometheus text exposition: every numeric or boolean leaf becomes a
+/// `systemcheck_<path>` gauge, array positions become an `index` label, and
+/// strings are skipped. Samples of one metric are grouped under a single
+/// `# TYPE` line, as the format requires.
+pub fn to_prometheus(value: &Value) -> String {
+    let mut families: Vec<(String, Vec<String>)> = Vec::new();
+    flatten(value, "", &mut |path, leaf| {
+        let sample = match leaf {
+            Value::Number(number) => number.to_string(),
+            Value::Bool(flag) => u8::from(*flag).to_string(),
+            _ => return,
+        };
+        let (name, labels) = prometheus_name(path);
+        let line = format!("{}{} {}\n", name, labels, sample);
+        match families.iter_mut().find(|(family, _)| *family == name) {
+            Some((_, lines)) => lines.push(line),
+            None => families.push((name, vec![line])),
+        }
+    });
+    families
+        .into_iter()
+        .map(|(name, lines)| format!("# TYPE {} gauge\n{}", name, lines.concat()))
+        .collect()
+}
+
+/// "disks[1].io_time_ms" -> ("systemcheck_disks_io_time_ms", "{index=\"1\"}")
+fn prometheus_name(path: &str) -> (String, String) {
+    let mut name = String::from("systemcheck");
+    let mut indices = Vec::new();
+    for segment in path.split('.') {
+        let (key, index) = match segment.split_once('[') {
+            Some((key, rest)) => (key, Some(rest.trim_end_matches(']'))),
+            None => (segment, None),
+        };
+        name.push('_');
+        name.extend(key.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }));
+        indices.extend(index);
+    }
+    let labels = if indices.is_empty() {
+        String::new()
+    } else {
+        format!("{{index=\"{}\"}}", indices.join(","))
+    };
+    (name, labels)
+}
+
+fn flatten(value: &Value, prefix: &str, emit: &mut impl FnMut(&str, &Value)) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(child, &path, emit);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, child) in items.iter().enumerate() {
+                flatten(child, &format!("{}[{}]", prefix, idx), emit);
+            }
+        }
+        leaf => emit(prefix, leaf),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Value {
+        serde_json::json!({
+            "version": "0.1.3",
+            "cpu": { "available_cpus": 4, "constrained": true, "quota": null },
+            "disks": [ { "device": "sda", "io_time_ms": 12 }, { "device": "sdb", "io_time_ms": 3 } ],
+            "warnings": []
+        })
+    }
+
+    #[test]
+    fn yaml_nests_objects_and_lists() {
+        let yaml = to_yaml(&sample());
+        assert_eq!(
+            yaml,
+            "cpu:\n  available_cpus: 4\n  constrained: true\n  quota: null\ndisks:\n- device: \"sda\"\n  io_time_ms: 12\n- device: \"sdb\"\n  io_time_ms: 3\nversion: \"0.1.3\"\nwarnings: []\n"
+        );
+    }
+
+    #[test]
+    fn flat_lists_every_leaf() {
+        let flat = to_flat(&sample());
+        assert!(flat.contains("version=0.1.3\n"));
+        assert!(flat.contains("cpu.quota=null\n"));
+        assert!(flat.contains("disks[0].device=sda\n"));
+    }
+
+    #[test]
+    fn prometheus_exports_numbers_and_booleans() {
+        let metrics = to_prometheus(&sample());
+        assert!(metrics.contains("# TYPE systemcheck_cpu_available_cpus gauge\nsystemcheck_cpu_available_cpus 4\n"));
+        assert!(metrics.contains("systemcheck_cpu_constrained 1\n"));
+        assert!(metrics.contains(
+            "# TYPE systemcheck_disks_io_time_ms gauge\nsystemcheck_disks_io_time_ms{index=\"0\"} 12\nsystemcheck_disks_io_time_ms{index=\"1\"} 3\n"
+        ));
+        assert!(!metrics.contains("version"));
+    }
+}
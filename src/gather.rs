@@ -0,0 +1,285 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::cgroupfs::{self, CgroupMounts};
+use crate::cpulist;
+use crate::diskstats::{self, DiskStat};
+
+/// Why a section could not be gathered. The CLI normally degrades to
+/// defaults; `--strict` reports these instead.
+#[derive(Debug)]
+pub enum GatherError {
+    /// A file exists (or should) but could not be read, e.g. permission denied
+    Io { path: String, source: io::Error },
+    /// A file was read but its contents were not in the expected format
+    Parse { path: String, detail: String },
+    /// This system does not expose the information at all
+    Unsupported(String),
+}
+
+impl fmt::Display for GatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GatherError::Io { path, source } => write!(f, "cannot read {}: {}", path, source),
+            GatherError::Parse { path, detail } => write!(f, "cannot parse {}: {}", path, detail),
+            GatherError::Unsupported(what) => write!(f, "unsupported: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for GatherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GatherError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn read(path: &str) -> Result<String, GatherError> {
+    fs::read_to_string(path).map_err(|source| GatherError::Io { path: path.to_string(), source })
+}
+
+/// Like `read`, but a missing file is not an error: many cgroup files only
+/// exist when the controller is enabled.
+fn read_optional(path: &str) -> Result<Option<String>, GatherError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(GatherError::Io { path: path.to_string(), source }),
+    }
+}
+
+fn parse_error(path: &str, detail: impl Into<String>) -> GatherError {
+    GatherError::Parse { path: path.to_string(), detail: detail.into() }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuSection {
+    /// CPUs present in the system, online or not
+    pub present: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemorySection {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// Limits set at exactly one cgroup level; None means "no limit".
+#[derive(Debug, Clone, PartialEq)]
+pub struct CgroupSection {
+    pub cpu_quota: Option<f64>,
+    pub memory_limit_bytes: Option<u64>,
+}
+
+/// Every section gathered independently, so one failure does not hide the
+/// others.
+#[derive(Debug)]
+pub struct Sections {
+    pub cpu: Result<CpuSection, GatherError>,
+    pub memory: Result<MemorySection, GatherError>,
+    pub cgroup: Result<CgroupSection, GatherError>,
+    pub disks: Result<Vec<DiskStat>, GatherError>,
+}
+
+impl Sections {
+    /// (section name, error) for every section that failed.
+    pub fn errors(&self) -> Vec<(&'static str, &GatherError)> {
+        [
+            ("cpu", self.cpu.as_ref().err()),
+            ("memory", self.memory.as_ref().err()),
+            ("cgroup", self.cgroup.as_ref().err()),
+            ("disks", self.disks.as_ref().err()),
+        ]
+        .into_iter()
+        .filter_map(|(section, err)| Some((section, err?)))
+        .collect()
+    }
+}
+
+pub fn gather_sections(cgroup_path: &str) -> Sections {
+    Sections {
+        cpu: gather_cpu(),
+        memory: gather_memory(),
+        cgroup: gather_cgroup(cgroup_path),
+        disks: gather_disks(),
+    }
+}
+
+pub fn gather_cpu() -> Result<CpuSection, GatherError> {
+    const PATH: &str = "/sys/devices/system/cpu/present";
+    let contents = read_optional(PATH)?
+        .ok_or_else(|| GatherError::Unsupported(format!("{} is missing", PATH)))?;
+    let present = match cpulist::parse_cpu_list(&contents).len() {
+        0 => return Err(parse_error(PATH, format!("no CPUs in list {:?}", contents.trim()))),
+        count => count,
+    };
+    Ok(CpuSection { present })
+}
+
+pub fn gather_memory() -> Result<MemorySection, GatherError> {
+    const PATH: &str = "/proc/meminfo";
+    let contents = read(PATH)?;
+    let value = |key: &str| {
+        contents.lines().find_map(|line| {
+            let (name, rest) = line.split_once(':')?;
+            if name.trim() != key {
+                return None;
+            }
+            Some(rest.split_whitespace().next().and_then(|kb| kb.parse::<u64>().ok()))
+        })
+    };
+    let kilobytes = |key: &str| match value(key) {
+        Some(Some(kb)) => Ok(kb * 1024),
+        Some(None) => Err(parse_error(PATH, format!("{} is not a number", key))),
+        None => Err(parse_error(PATH, format!("{} is missing", key))),
+    };
+    // Kernels before 3.14 lack MemAvailable; estimate it as the lenient reader does
+    let available_bytes = match value("MemAvailable") {
+        None => {
+            let optional = |key: &str| if value(key).is_some() { kilobytes(key) } else { Ok(0) };
+            (kilobytes("MemFree")? + optional("Buffers")? + optional("Cached")?).saturating_sub(optional("Shmem")?)
+        }
+        Some(_) => kilobytes("MemAvailable")?,
+    };
+    Ok(MemorySection {
+        total_bytes: kilobytes("MemTotal")?,
+        available_bytes,
+    })
+}
+
+/// Reads `cpu.max`/`memory.max` (v2) or the v1 equivalents at this level.
+/// Missing files mean no limit; unreadable or malformed ones are errors.
+pub fn gather_cgroup(cgroup_path: &str) -> Result<CgroupSection, GatherError> {
+    if read_optional("/proc/self/cgroup")?.is_none() {
+        return Err(GatherError::Unsupported("/proc/self/cgroup is missing".to_string()));
+    }
+
+    Ok(CgroupSection {
+        cpu_quota: read_cpu_quota(cgroup_path)?,
+        memory_limit_bytes: read_memory_limit(cgroup_path)?,
+    })
+}
+
+/// CPU quota set at exactly this cgroup level, in CPUs.
+pub fn read_cpu_quota(cgroup_path: &str) -> Result<Option<f64>, GatherError> {
+    read_cpu_quota_in(cgroupfs::mounts(), cgroup_path)
+}
+
+pub fn read_cpu_quota_in(mounts: &CgroupMounts, cgroup_path: &str) -> Result<Option<f64>, GatherError> {
+    if mounts.hierarchy("cpu") != Some("v1")
+        && let Some(cpu_max) = mounts.v2_file(cgroup_path, "cpu.max")
+        && let Some(contents) = read_optional(&cpu_max)?
+    {
+        return parse_cpu_max(&cpu_max, &contents);
+    }
+    match mounts.v1_dir("cpu", cgroup_path) {
+        Some(dir) => read_cfs_quota(&dir).map(Option::flatten),
+        None => Ok(None),
+    }
+}
+
+/// v1 `cpu.cfs_quota_us`/`cpu.cfs_period_us` in `dir`: None when either file
+/// is missing, Some(None) for an explicit -1 (unlimited at this level).
+pub fn read_cfs_quota(dir: &str) -> Result<Option<Option<f64>>, GatherError> {
+    let quota_path = format!("{}/cpu.cfs_quota_us", dir);
+    let period_path = format!("{}/cpu.cfs_period_us", dir);
+    let (Some(quota), Some(period)) = (read_optional(&quota_path)?, read_optional(&period_path)?) else {
+        return Ok(None);
+    };
+    let quota = parse_number::<i64>(&quota_path, &quota)?;
+    let period = parse_number::<i64>(&period_path, &period)?;
+    match (quota, period) {
+        (-1, _) => Ok(Some(None)),
+        (quota, period) if quota > 0 && period > 0 => Ok(Some(Some(quota as f64 / period as f64))),
+        _ => Err(parse_error(&quota_path, format!("expected a positive quota or -1, found {} / {}", quota, period))),
+    }
+}
+
+/// Memory limit set at exactly this cgroup level, in bytes.
+pub fn read_memory_limit(cgroup_path: &str) -> Result<Option<u64>, GatherError> {
+    read_memory_limit_in(cgroupfs::mounts(), cgroup_path)
+}
+
+pub fn read_memory_limit_in(mounts: &CgroupMounts, cgroup_path: &str) -> Result<Option<u64>, GatherError> {
+    if mounts.hierarchy("memory") != Some("v1")
+        && let Some(memory_max) = mounts.v2_file(cgroup_path, "memory.max")
+        && let Some(contents) = read_optional(&memory_max)?
+    {
+        if contents.trim() == "max" {
+            return Ok(None);
+        }
+        return parse_number::<u64>(&memory_max, &contents).map(Some);
+    }
+    let Some(v1) = mounts.v1_file("memory", cgroup_path, "memory.limit_in_bytes") else {
+        return Ok(None);
+    };
+    match read_optional(&v1)? {
+        Some(contents) => Ok(Some(parse_number::<u64>(&v1, &contents)?).filter(|&limit| !is_v1_unlimited(limit))),
+        None => Ok(None),
+    }
+}
+
+/// v1 reports "unlimited" as i64::MAX rounded down to the page size, so the
+/// exact value depends on the machine (9223372036854771712 with 4 KiB pages,
+/// 9223372036854710272 with 64 KiB). Anything within 1 GiB of i64::MAX covers
+/// every page size and is far beyond any real limit.
+pub fn is_v1_unlimited(bytes: u64) -> bool {
+    bytes >= V1_UNLIMITED_FLOOR
+}
+
+const V1_UNLIMITED_FLOOR: u64 = i64::MAX as u64 & !((1 << 30) - 1);
+
+pub fn gather_disks() -> Result<Vec<DiskStat>, GatherError> {
+    read("/proc/diskstats").map(|contents| diskstats::parse_diskstats(&contents))
+}
+
+fn parse_number<T: std::str::FromStr>(path: &str, contents: &str) -> Result<T, GatherError> {
+    contents
+        .trim()
+        .parse::<T>()
+        .map_err(|_| parse_error(path, format!("expected a number, found {:?}", contents.trim())))
+}
+
+/// `cpu.max` is "<quota> <period>" or "max <period>".
+pub fn parse_cpu_max(path: &str, contents: &str) -> Result<Option<f64>, GatherError> {
+    let mut parts = contents.split_whitespace();
+    let (Some(quota), Some(period), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(parse_error(path, format!("expected \"<quota> <period>\", found {:?}", contents.trim())));
+    };
+    let period = parse_number::<u64>(path, period)?;
+    if quota == "max" {
+        return Ok(None);
+    }
+    let quota = parse_number::<u64>(path, quota)?;
+    if period == 0 {
+        return Err(parse_error(path, "period is zero"));
+    }
+    Ok(Some(quota as f64 / period as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_unlimited_sentinel_depends_on_page_size() {
+        // i64::MAX rounded down to 4 KiB and to 64 KiB pages
+        assert!(is_v1_unlimited(9223372036854771712));
+        assert!(is_v1_unlimited(9223372036854710272));
+        assert!(is_v1_unlimited(u64::MAX));
+        assert!(!is_v1_unlimited(512 << 20));
+        assert!(!is_v1_unlimited(64 << 40));
+    }
+
+    #[test]
+    fn cpu_max_distinguishes_unlimited_from_malformed() {
+        assert_eq!(parse_cpu_max("cpu.max", "150000 100000\n").unwrap(), Some(1.5));
+        assert_eq!(parse_cpu_max("cpu.max", "max 100000\n").unwrap(), None);
+        assert!(matches!(parse_cpu_max("cpu.max", "150000"), Err(GatherError::Parse { .. })));
+        assert!(matches!(parse_cpu_max("cpu.max", "lots 100000"), Err(GatherError::Parse { .. })));
+        assert!(matches!(parse_cpu_max("cpu.max", "1000 0"), Err(GatherError::Parse { .. })));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+const SYS_DRM: &str = "/sys/class/drm";
+const PROC_NVIDIA_GPUS: &str = "/proc/driver/nvidia/gpus";
+
+/// A GPU visible to this process. `vendor` is a name for well-known PCI
+/// vendors and the raw id (e.g. "0x1a03") otherwise; `device` is the PCI
+/// device id from sysfs, or the model name from the NVIDIA driver when only
+/// procfs is available.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct GpuInfo {
+    pub vendor: String,
+    pub device: String,
+}
+
+/// Best-effort GPU listing from DRM sysfs, plus NVIDIA GPUs that only the
+/// proprietary driver's procfs knows about (no nvidia-drm module loaded).
+pub fn read_gpus() -> Vec<GpuInfo> {
+    let mut gpus = Vec::new();
+    let mut seen = BTreeSet::new();
+    if let Ok(entries) = fs::read_dir(SYS_DRM) {
+        let mut cards: Vec<_> = entries
+            .flatten()
+            .filter(|entry| is_card_name(&entry.file_name().to_string_lossy()))
+            .collect();
+        cards.sort_by_key(|entry| entry.file_name());
+        for card in cards {
+            let device_dir = card.path().join("device");
+            let read = |file: &str| fs::read_to_string(device_dir.join(file)).ok().map(|value| value.trim().to_string());
+            let (Some(vendor), Some(device)) = (read("vendor"), read("device")) else {
+                continue;
+            };
+            // The device link points at the PCI address, e.g. .../0000:3b:00.0
+            if let Ok(target) = fs::canonicalize(&device_dir)
+                && let Some(address) = target.file_name()
+            {
+                seen.insert(address.to_string_lossy().to_lowercase());
+            }
+            gpus.push(GpuInfo { vendor: vendor_name(&vendor), device });
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(PROC_NVIDIA_GPUS) {
+        let mut nvidia: Vec<_> = entries.flatten().collect();
+        nvidia.sort_by_key(|entry| entry.file_name());
+        for entry in nvidia {
+            if seen.contains(&entry.file_name().to_string_lossy().to_lowercase()) {
+                continue;
+            }
+            let model = fs::read_to_string(entry.path().join("information"))
+                .ok()
+                .and_then(|info| parse_nvidia_model(&info))
+                .unwrap_or_else(|| "unknown".to_string());
+            gpus.push(GpuInfo { vendor: "NVIDIA".to_string(), device: model });
+        }
+    }
+    gpus
+}
+
+/// True when `/dev/nvidia0` (etc.) exists, i.e. a GPU was passed into a
+/// container even if sysfs and procfs hide it.
+pub fn nvidia_device_nodes_present() -> bool {
+    fs::read_dir("/dev")
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.strip_prefix("nvidia").is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()))
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// "card0" but not connector entries such as "card0-HDMI-A-1".
+fn is_card_name(name: &str) -> bool {
+    name.strip_prefix("card")
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn vendor_name(id: &str) -> String {
+    match id.to_ascii_lowercase().as_str() {
+        "0x10de" => "NVIDIA".to_string(),
+        "0x1002" => "AMD".to_string(),
+        "0x8086" => "Intel".to_string(),
+        "0x1af4" => "virtio".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// The "Model:" line of `/proc/driver/nvidia/gpus/<addr>/information`.
+fn parse_nvidia_model(information: &str) -> Option<String> {
+    information.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "Model").then(|| value.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn card_names_exclude_connectors() {
+        assert!(is_card_name("card0"));
+        assert!(is_card_name("card12"));
+        assert!(!is_card_name("card0-HDMI-A-1"));
+        assert!(!is_card_name("renderD128"));
+    }
+
+    #[test]
+    fn nvidia_model_from_information_file() {
+        let info = "Model: \t\t Tesla T4\nIRQ:   \t\t 38\nGPU UUID: \t GPU-1234\n";
+        assert_eq!(parse_nvidia_model(info).as_deref(), Some("Tesla T4"));
+        assert_eq!(vendor_name("0x10DE"), "NVIDIA");
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{cgroupfs, gather};
+
+const SYS_HUGEPAGES: &str = "/sys/kernel/mm/hugepages";
+
+/// One huge page pool. `total`, `free` and `reserved` count pages, not bytes;
+/// `reserved` pages are promised to a mapping but not yet faulted in.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct HugePagePool {
+    pub size_bytes: u64,
+    pub total: u64,
+    pub free: u64,
+    pub reserved: u64,
+}
+
+impl HugePagePool {
+    /// Memory set aside for this pool, which normal allocations cannot use.
+    pub fn pool_bytes(&self) -> u64 {
+        self.total.saturating_mul(self.size_bytes)
+    }
+}
+
+/// Reads every pool under `/sys/kernel/mm/hugepages`, falling back to the
+/// default-size pool in `/proc/meminfo` when sysfs is unavailable. Sorted by
+/// page size.
+pub fn read_hugepage_pools() -> Vec<HugePagePool> {
+    let mut pools = read_sysfs_pools(Path::new(SYS_HUGEPAGES));
+    if pools.is_empty()
+        && let Ok(contents) = fs::read_to_string("/proc/meminfo")
+    {
+        pools.extend(parse_meminfo_pool(&contents));
+    }
+    pools.sort_by_key(|pool| pool.size_bytes);
+    pools
+}
+
+fn read_sysfs_pools(root: &Path) -> Vec<HugePagePool> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            // Directory names look like "hugepages-2048kB"
+            let name = entry.file_name();
+            let size_kb = name
+                .to_str()?
+                .strip_prefix("hugepages-")?
+                .strip_suffix("kB")?
+                .parse::<u64>()
+                .ok()?;
+            let read = |file: &str| {
+                fs::read_to_string(entry.path().join(file))
+                    .ok()
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+            };
+            Some(HugePagePool {
+                size_bytes: size_kb * 1024,
+                total: read("nr_hugepages")?,
+                free: read("free_hugepages").unwrap_or(0),
+                reserved: read("resv_hugepages").unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// The default pool from `HugePages_Total`/`_Free`/`_Rsvd` (page counts, no
+/// unit) and `Hugepagesize` (kB).
+fn parse_meminfo_pool(contents: &str) -> Option<HugePagePool> {
+    let value = |key: &str| {
+        contents.lines().find_map(|line| {
+            let (name, rest) = line.split_once(':')?;
+            if name.trim() != key {
+                return None;
+            }
+            rest.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    Some(HugePagePool {
+        size_bytes: value("Hugepagesize")? * 1024,
+        total: value("HugePages_Total")?,
+        free: value("HugePages_Free").unwrap_or(0),
+        reserved: value("HugePages_Rsvd").unwrap_or(0),
+    })
+}
+
+/// A hugetlb controller entry for one page size. Only sizes with a limit set
+/// or pages in use are reported.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct HugetlbLimit {
+    /// As the kernel names it: "2MB", "1GB", "64KB"
+    pub page_size: String,
+    pub limit_bytes: Option<u64>,
+    pub usage_bytes: u64,
+}
+
+/// Reads `hugetlb.<size>.max`/`.current` (v2) or the v1
+/// `hugetlb.<size>.limit_in_bytes`/`.usage_in_bytes` for one cgroup.
+pub fn read_hugetlb_limits(cgroup_path: &str) -> Vec<HugetlbLimit> {
+    let mounts = cgroupfs::mounts();
+    let mut limits = match mounts.v2_dir(cgroup_path) {
+        Some(v2) => read_hugetlb_dir(Path::new(&v2), "max", "current"),
+        None => Vec::new(),
+    };
+    if limits.is_empty()
+        && let Some(v1) = mounts.v1_dir("hugetlb", cgroup_path)
+    {
+        limits = read_hugetlb_dir(Path::new(&v1), "limit_in_bytes", "usage_in_bytes");
+    }
+    limits.sort_by_key(|limit| parse_page_size(&limit.page_size));
+    limits
+}
+
+fn read_hugetlb_dir(dir: &Path, limit_file: &str, usage_file: &str) -> Vec<HugetlbLimit> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let suffix = format!(".{}", limit_file);
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            // "hugetlb.2MB.max"; the "hugetlb.2MB.rsvd.max" variants are skipped
+            let name = entry.file_name();
+            let page_size = name.to_str()?.strip_prefix("hugetlb.")?.strip_suffix(suffix.as_str())?;
+            parse_page_size(page_size)?;
+            let read = |file: String| fs::read_to_string(dir.join(file)).ok();
+            let limit = read(format!("hugetlb.{}.{}", page_size, limit_file))?;
+            let limit_bytes = limit
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .filter(|&bytes| !gather::is_v1_unlimited(bytes));
+            let usage_bytes = read(format!("hugetlb.{}.{}", page_size, usage_file))
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .unwrap_or(0);
+            if limit_bytes.is_none() && usage_bytes == 0 {
+                return None;
+            }
+            Some(HugetlbLimit {
+                page_size: page_size.to_string(),
+                limit_bytes,
+                usage_bytes,
+            })
+        })
+        .collect()
+}
+
+/// "2MB" -> 2 MiB. The kernel uses binary multiples with KB/MB/GB suffixes.
+fn parse_page_size(size: &str) -> Option<u64> {
+    let split = size.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = size.split_at(split);
+    let multiplier = match unit {
+        "KB" => 1024,
+        "MB" => 1024 * 1024,
+        "GB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_size_uses_kernel_suffixes() {
+        assert_eq!(parse_page_size("2MB"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_page_size("1GB"), Some(1024 * 1024 * 1024));
+        assert_eq!(parse_page_size("64KB"), Some(64 * 1024));
+        assert_eq!(parse_page_size("2MB.rsvd"), None);
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+use std::fs;
+
+/// Instruction-set extensions worth surfacing, in display order.
+const CURATED_FEATURES: &[&str] = &[
+    "sse4_2", "avx", "avx2", "avx512f", "fma", "aes", "neon", "sve", "sve2",
+];
+
+/// x86-64 psABI micro-architecture levels, as `/proc/cpuinfo` flag names.
+/// `pni` is SSE3 and `abm` implies LZCNT.
+const X86_64_V1: &[&str] = &["cmov", "cx8", "fpu", "fxsr", "mmx", "sse", "sse2"];
+const X86_64_V2: &[&str] = &["cx16", "lahf_lm", "popcnt", "pni", "sse4_1", "sse4_2", "ssse3"];
+const X86_64_V3: &[&str] = &["avx", "avx2", "bmi1", "bmi2", "f16c", "fma", "abm", "movbe", "xsave"];
+const X86_64_V4: &[&str] = &["avx512f", "avx512bw", "avx512cd", "avx512dq", "avx512vl"];
+
+pub struct IsaInfo {
+    pub features: Vec<String>,
+    pub x86_64_level: Option<u8>,
+}
+
+pub fn read_isa_info() -> IsaInfo {
+    let flags = fs::read_to_string("/proc/cpuinfo")
+        .map(|contents| parse_cpu_flags(&contents))
+        .unwrap_or_default();
+    IsaInfo {
+        features: curated_features(&flags),
+        x86_64_level: x86_64_level(&flags),
+    }
+}
+
+/// Returns the flag set of the first CPU: `flags` on x86, `Features` on ARM.
+fn parse_cpu_flags(contents: &str) -> HashSet<String> {
+    contents
+        .lines()
+        .find(|line| line.starts_with("flags") || line.starts_with("Features"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, flags)| flags.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn curated_features(flags: &HashSet<String>) -> Vec<String> {
+    CURATED_FEATURES
+        .iter()
+        .filter(|&&feature| {
+            // aarch64 reports NEON as "asimd"
+            flags.contains(feature) || (feature == "neon" && flags.contains("asimd"))
+        })
+        .map(|feature| feature.to_string())
+        .collect()
+}
+
+fn x86_64_level(flags: &HashSet<String>) -> Option<u8> {
+    let has_all = |required: &[&str]| required.iter().all(|flag| flags.contains(*flag));
+    if !has_all(X86_64_V1) {
+        return None;
+    }
+    let mut level = 1;
+    for (next, required) in [(2, X86_64_V2), (3, X86_64_V3), (4, X86_64_V4)] {
+        if !has_all(required) {
+            break;
+        }
+        level = next;
+    }
+    Some(level)
+}
@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+const SYS_KSM: &str = "/sys/kernel/mm/ksm";
+
+/// Kernel samepage merging. With KSM on, identical pages (common across VM
+/// guests) are shared, so used memory adds up to more than the RAM it takes.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct KsmInfo {
+    /// `run` is 1 (merging); 0 (stopped) and 2 (unmerge everything) both count as off
+    pub enabled: bool,
+    /// `pages_shared`: distinct pages KSM keeps
+    pub pages_shared: u64,
+    /// `pages_sharing`: extra mappings pointing at them, i.e. pages saved
+    pub pages_sharing: u64,
+    /// `pages_sharing` × page size
+    pub saved_bytes: u64,
+}
+
+/// None when the kernel has no KSM (`/sys/kernel/mm/ksm` is missing).
+pub fn read_ksm(page_size: u64) -> Option<KsmInfo> {
+    read_ksm_in(Path::new(SYS_KSM), page_size)
+}
+
+fn read_ksm_in(dir: &Path, page_size: u64) -> Option<KsmInfo> {
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok().and_then(|value| value.trim().parse::<u64>().ok());
+    let run = read("run")?;
+    let pages_sharing = read("pages_sharing").unwrap_or(0);
+    Some(KsmInfo {
+        enabled: run == 1,
+        pages_shared: read("pages_shared").unwrap_or(0),
+        pages_sharing,
+        saved_bytes: pages_sharing.saturating_mul(page_size),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::fixture_dir;
+
+    #[test]
+    fn saved_bytes_counts_sharing_pages() {
+        let dir = fixture_dir("ksm", &[("run", "1\n"), ("pages_shared", "100\n"), ("pages_sharing", "2500\n")]);
+        let ksm = read_ksm_in(&dir, 4096).unwrap();
+        assert!(ksm.enabled);
+        assert_eq!(ksm.saved_bytes, 2500 * 4096);
+
+        fs::write(dir.join("run"), "2\n").unwrap();
+        assert!(!read_ksm_in(&dir, 4096).unwrap().enabled);
+        assert_eq!(read_ksm_in(&dir.join("missing"), 4096), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
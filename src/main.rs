@@ -1,9 +1,41 @@
-use std::collections::HashSet;
+mod cgroupfs;
+mod coretypes;
+mod cpucache;
+mod cpufreq;
+mod cpulist;
+mod diskstats;
+mod formats;
+mod gather;
+mod gpu;
+mod hugepages;
+mod isa;
+mod ksm;
+mod manifest;
+mod memtrend;
+mod mitigations;
+mod mountinfo;
+mod numa;
+mod overcommit;
+mod probelog;
+mod procstat;
+mod profile;
+mod procs;
+mod psi;
+mod rawdump;
+mod rlimits;
+mod shm;
+mod timestamp;
+mod tmpfs;
+#[cfg(test)]
+mod testutil;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
 use clap::Parser;
-use humanize_bytes::humanize_bytes_binary;
+use humanize_bytes::{humanize_bytes_binary, humanize_bytes_decimal};
+use schemars::JsonSchema;
 use serde::Serialize;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -11,624 +43,4637 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[derive(Parser, Debug)]
 #[command(name = "systemcheck", version)]
 struct Cli {
-    /// Verbose output (detailed sections)
-    #[arg(short = 'v', long = "verbose")]
-    verbose: bool,
+    /// Verbose output (detailed sections); repeat (-vv) for more detail
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Output format for the report
+    #[arg(long = "format", alias = "output-format", value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 
-    /// Emit JSON to stdout
-    #[arg(long = "json")]
+    /// Print a JSON Schema describing the JSON reports and exit
+    #[arg(long = "json-schema", exclusive = true)]
+    json_schema: bool,
+
+    /// Deprecated: same as --format json
+    #[arg(long = "json", conflicts_with = "format")]
     json: bool,
+
+    /// Print a single stable summary line (for logs and alerts)
+    #[arg(long = "summary", conflicts_with_all = ["json", "format"])]
+    summary: bool,
+
+    /// Count only performance cores when recommending a worker count
+    #[arg(long = "perf-cores-only")]
+    perf_cores_only: bool,
+
+    /// Keep running and print only fields that change (limits, constraints, usage thresholds)
+    #[arg(long = "diff-live")]
+    diff_live: bool,
+
+    /// Keep running and print a status line every interval (throttling shown as per-interval deltas)
+    #[arg(long = "watch", conflicts_with = "diff_live")]
+    watch: bool,
+
+    /// Collect N detailed reports, --interval apart, and print them as a JSON array
+    #[arg(long = "samples", value_name = "N", conflicts_with_all = ["watch", "diff_live", "summary"],
+          value_parser = clap::value_parser!(u32).range(1..))]
+    samples: Option<u32>,
+
+    /// With --samples, stream one JSON object per line as each sample is taken
+    #[arg(long = "ndjson", requires = "samples")]
+    ndjson: bool,
+
+    /// Seconds between samples in continuous modes
+    #[arg(long = "interval", value_name = "SECS", default_value_t = 2.0, value_parser = parse_interval)]
+    interval: f64,
+
+    /// Milliseconds to sample /proc/stat for CPU steal time and per-CPU usage (verbose only)
+    #[arg(long = "steal-sample-ms", value_name = "MS", default_value_t = 250)]
+    steal_sample_ms: u64,
+
+    /// Read memory.current N times over a short window and report the median
+    #[arg(long = "stable-reads", value_name = "N", default_value_t = 1,
+          value_parser = clap::value_parser!(u32).range(1..))]
+    stable_reads: u32,
+
+    /// How byte values are formatted in text output (JSON is always raw bytes)
+    #[arg(long = "units", value_enum, default_value_t = Units::Binary)]
+    units: Units,
+
+    /// Exit nonzero when no CPU quota or memory limit applies to the current cgroup
+    #[arg(long = "fail-if-unconstrained")]
+    fail_if_unconstrained: bool,
+
+    /// Only count limits set on the current cgroup itself, not a memory limit inherited from an ancestor or a CPU quota from the root cgroup
+    #[arg(long = "no-fallback-to-root")]
+    no_fallback_to_root: bool,
+
+    /// Report limits and usage for this cgroup (e.g. /system.slice/foo.service) instead of our own
+    #[arg(long = "cgroup-path", value_name = "PATH", value_parser = parse_cgroup_path)]
+    cgroup_path: Option<String>,
+
+    /// Warn when the cgroup's non-reclaimable memory passes this percentage of its hard limit (e.g. 90),
+    /// or, given a size (e.g. 500MiB), when less than that is left under the limit
+    #[arg(long = "mem-warn-threshold", value_name = "PERCENT|SIZE", default_value = "90",
+          value_parser = parse_mem_warn_threshold)]
+    mem_warn_threshold: MemWarnThreshold,
+
+    /// Preflight: exit nonzero unless at least this many effective CPUs are available
+    #[arg(long = "min-cpus", value_name = "N")]
+    min_cpus: Option<f64>,
+
+    /// Preflight: exit nonzero unless the effective memory ceiling is at least SIZE (e.g. 8G, 512M)
+    #[arg(long = "min-memory", value_name = "SIZE", value_parser = parse_byte_size)]
+    min_memory: Option<u64>,
+
+    /// Print a stable key=value block of everything that affects benchmark results
+    #[arg(long = "bench-env", conflicts_with_all = ["json", "format", "summary"])]
+    bench_env: bool,
+
+    /// Dump every cgroup file consulted for the current cgroup, for bug reports
+    #[arg(long = "raw", conflicts_with_all = ["json", "format", "summary", "bench_env"])]
+    raw: bool,
+
+    /// List the N processes in the current cgroup using the most memory (RSS, default 5); reads every /proc/<pid>/status
+    #[arg(long = "top-consumers", value_name = "N", num_args = 0..=1, default_missing_value = "5",
+          conflicts_with_all = ["json", "format", "summary", "bench_env", "raw"])]
+    top_consumers: Option<usize>,
+
+    /// Print the limits of two cgroups side by side, marking the ones that differ
+    #[arg(long = "compare-cgroup", num_args = 2, value_names = ["PATH_A", "PATH_B"],
+          conflicts_with_all = ["summary", "bench_env", "raw", "top_consumers"])]
+    compare_cgroup: Option<Vec<String>>,
+
+    /// Shared-memory mount to report on
+    #[arg(long = "shm-path", value_name = "PATH", default_value = shm::DEFAULT_SHM_PATH)]
+    shm_path: String,
+
+    /// Exit nonzero unless the cgroup CPU quota equals N CPUs (within --assert-cpus-tolerance)
+    #[arg(long = "assert-cpus", value_name = "N")]
+    assert_cpus: Option<f64>,
+
+    /// Allowed difference, in CPUs, for --assert-cpus and the `cpus` of --check-config
+    #[arg(long = "assert-cpus-tolerance", value_name = "CPUS", default_value_t = 0.15)]
+    assert_cpus_tolerance: f64,
+
+    /// Exit nonzero, printing a diff, unless the cgroup's CPU quota, memory limit and pids limit match the
+    /// `cpus`, `memory` and `pids` in this TOML or JSON manifest ("max" for no limit)
+    #[arg(long = "check-config", value_name = "FILE", value_parser = parse_check_config)]
+    check_config: Option<CheckConfig>,
+
+    /// Print the report and also apply the checks (--fail-if-unconstrained, --assert-cpus, --min-cpus,
+    /// --min-memory, --check-config, --mem-warn-threshold), exiting 1 after the report when any fails
+    #[arg(long = "gate", conflicts_with_all = ["watch", "diff_live", "samples", "raw", "top_consumers", "compare_cgroup"])]
+    gate: bool,
+
+    /// Environment to tune notes and warnings for (a missing limit is expected on bare metal, suspicious in a container);
+    /// detected from container and hypervisor markers when omitted
+    #[arg(long = "profile", value_enum)]
+    profile: Option<profile::Profile>,
+
+    /// Exit nonzero, listing each failure, when a source file is unreadable or malformed instead of falling back to defaults.
+    /// With --strict or --verbose, each unreadable file is also logged to stderr once, with its errno
+    #[arg(long = "strict")]
+    strict: bool,
+}
+
+impl Cli {
+    /// `--format`, with the deprecated `--json` folded in.
+    fn output_format(&self) -> OutputFormat {
+        if self.json { OutputFormat::Json } else { self.format }
+    }
+
+    /// `--profile`, else the detected environment.
+    fn profile(&self) -> profile::Profile {
+        self.profile.unwrap_or_else(profile::detect)
+    }
+}
+
+/// Accepts a cgroup path that exists in a mounted cgroup hierarchy (v2, or
+/// any v1 controller).
+fn parse_cgroup_path(value: &str) -> Result<String, String> {
+    if !value.starts_with('/') {
+        return Err(format!("'{}' must be an absolute cgroup path such as /system.slice", value));
+    }
+    let path = value.trim_end_matches('/');
+    let path = if path.is_empty() { "/" } else { path };
+    if !cgroup_exists(path) {
+        return Err(format!("no cgroup at '{}' in any mounted cgroup hierarchy", path));
+    }
+    Ok(path.to_string())
+}
+
+fn cgroup_exists(cgroup_path: &str) -> bool {
+    let mounts = cgroupfs::mounts();
+    mounts
+        .unified
+        .iter()
+        .chain(mounts.controllers.values())
+        .any(|root| Path::new(&format!("{}{}", root, cgroup_path)).is_dir())
+}
+
+/// Shortest `--interval`; anything less makes `--watch` and `--diff-live`
+/// spin re-reading cgroup files.
+const MIN_INTERVAL_SECS: f64 = 0.1;
+
+fn parse_interval(value: &str) -> Result<f64, String> {
+    let secs: f64 = value.trim().parse().map_err(|_| format!("invalid number of seconds '{}'", value))?;
+    if !secs.is_finite() || secs < MIN_INTERVAL_SECS {
+        return Err(format!("interval must be at least {}s", MIN_INTERVAL_SECS));
+    }
+    Ok(secs)
+}
+
+/// Parses sizes like "8G", "512M", "1.5GiB" or "4096" (binary multiples).
+fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("invalid size '{}'", value))?;
+    let multiplier: u64 = match suffix.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        other => return Err(format!("unknown size suffix '{}' (use K, M, G or T)", other)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// `--check-config`: the limits a manifest says the environment should have.
+/// Keys the manifest leaves out are not checked.
+#[derive(Clone, Debug, PartialEq)]
+struct CheckConfig {
+    path: String,
+    cpus: Option<manifest::Expected<f64>>,
+    memory: Option<manifest::Expected<u64>>,
+    pids: Option<manifest::Expected<u64>>,
+}
+
+fn parse_check_config(path: &str) -> Result<CheckConfig, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path, err))?;
+    let entries = manifest::parse(&contents).map_err(|err| format!("{}: {}", path, err))?;
+    let mut config = CheckConfig { path: path.to_string(), cpus: None, memory: None, pids: None };
+    for (key, value) in entries {
+        match key.as_str() {
+            "cpus" => {
+                config.cpus = Some(manifest::Expected::parse(&value, |cpus| {
+                    cpus.parse::<f64>().ok().filter(|&cpus| cpus > 0.0).ok_or_else(|| format!("invalid CPU count '{}'", cpus))
+                })?)
+            }
+            "memory" => config.memory = Some(manifest::Expected::parse(&value, parse_byte_size)?),
+            "pids" => {
+                config.pids = Some(manifest::Expected::parse(&value, |pids| {
+                    pids.parse::<u64>().map_err(|_| format!("invalid pids limit '{}'", pids))
+                })?)
+            }
+            other => return Err(format!("{}: unknown key '{}' (expected cpus, memory or pids)", path, other)),
+        }
+    }
+    if config.cpus.is_none() && config.memory.is_none() && config.pids.is_none() {
+        return Err(format!("{}: no cpus, memory or pids limits to check", path));
+    }
+    Ok(config)
+}
+
+/// `--mem-warn-threshold`: how close to its hard memory limit a cgroup may get
+/// before the report flags it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MemWarnThreshold {
+    /// Usage at or above this percentage of the limit
+    Percent(f64),
+    /// Less than this many bytes left under the limit
+    Headroom(u64),
+}
+
+impl MemWarnThreshold {
+    fn exceeded(self, usage: u64, limit: u64) -> bool {
+        match self {
+            MemWarnThreshold::Percent(percent) => usage as f64 >= limit as f64 * percent / 100.0,
+            MemWarnThreshold::Headroom(bytes) => limit.saturating_sub(usage) < bytes,
+        }
+    }
+}
+
+/// A bare number (optionally with "%") is a percentage; anything with a size
+/// suffix is a headroom in bytes.
+fn parse_mem_warn_threshold(value: &str) -> Result<MemWarnThreshold, String> {
+    let trimmed = value.trim();
+    if let Ok(percent) = trimmed.trim_end_matches('%').parse::<f64>() {
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(format!("percentage must be in (0, 100], got {}", percent));
+        }
+        return Ok(MemWarnThreshold::Percent(percent));
+    }
+    parse_byte_size(trimmed).map(MemWarnThreshold::Headroom)
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OutputFormat {
+    /// Human-readable report
+    Text,
+    /// Pretty-printed JSON
+    Json,
+    /// The JSON report as YAML
+    Yaml,
+    /// Prometheus text exposition (numeric and boolean fields as gauges)
+    Prometheus,
+    /// One dotted.key=value line per field
+    Flat,
+}
+
+/// Version of the JSON report layout, shown in the `--json-schema` title.
+/// Bumped when a field is removed or changes meaning; new fields do not bump it.
+const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema (draft 2020-12) matching either the simple or the detailed
+/// report, generated from the report structs so it cannot drift from them.
+fn report_json_schema() -> serde_json::Value {
+    let mut generator = schemars::generate::SchemaSettings::draft2020_12().into_generator();
+    let simple = generator.subschema_for::<SimpleReport>();
+    let detailed = generator.subschema_for::<DetailedReport>();
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("systemcheck report (schema version {})", SCHEMA_VERSION),
+        "description": "Output of `systemcheck --format json` (SimpleReport) or `systemcheck -v --format json` (DetailedReport)",
+        "anyOf": [simple, detailed],
+        "$defs": generator.take_definitions(true),
+    })
+}
+
+/// Renders a serialized report in a machine-readable format.
+fn render_report(value: &serde_json::Value, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => unreachable!("text output is printed section by section"),
+        OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(value).unwrap()),
+        OutputFormat::Yaml => formats::to_yaml(value),
+        OutputFormat::Prometheus => formats::to_prometheus(value),
+        OutputFormat::Flat => formats::to_flat(value),
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Units {
+    /// KiB, MiB, GiB
+    Binary,
+    /// KB, MB, GB
+    Decimal,
+    /// Plain byte counts
+    Bytes,
+}
+
+/// Formats a byte count for text output. Every human-readable byte value goes
+/// through here so `--units` applies consistently.
+fn format_bytes(bytes: u64, units: Units) -> String {
+    match units {
+        Units::Binary => humanize_bytes_binary!(bytes).to_string(),
+        Units::Decimal => humanize_bytes_decimal!(bytes).to_string(),
+        Units::Bytes => format!("{} B", bytes),
+    }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct SimpleCpuSummary {
     available_cpus: usize,
     system_logical_cpus: usize,
     constrained: bool,
+    sub_core_quota: bool,
+    /// What limits the CPUs: "cpu.max quota", "cpuset", "affinity" or "cpu.idle"
+    constraint_reason: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct SimpleMemorySummary {
+    /// `MemAvailable`: free RAM plus reclaimable cache, not just unused RAM
     system_available_bytes: u64,
     cgroup_memory_limit_bytes: Option<u64>,
+    /// How much can still be allocated: min(limit - usage, MemAvailable)
+    effective_available_bytes: u64,
+    cgroup_memory_usage_bytes: Option<u64>,
+    /// Usage as a percentage of `cgroup_memory_limit_bytes`, 0-100 with one decimal
+    usage_percent_of_limit: Option<f64>,
     constrained: bool,
+    /// "max" for a hard memory.max limit, "high" when only memory.high is set
+    limit_kind: Option<String>,
+    /// "hard" (OOM at the limit), "soft" (throttled above memory.high, no hard cap) or "none"
+    enforcement: String,
+    /// Non-reclaimable usage has crossed `--mem-warn-threshold` for the hard limit
+    memory_pressure_warning: bool,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct SimpleReport {
     version: String,
+    /// When the report was taken, ISO-8601 UTC
+    timestamp: String,
     cpu: SimpleCpuSummary,
     memory: SimpleMemorySummary,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct DetailedCpuInfo {
+    /// CPUs present in the system, including offline ones
     system_logical_cpus: usize,
+    system_online_cpus: usize,
     system_physical_cpus: usize,
     available_cpus: usize,
     cgroup_cpu_quota: Option<f64>,
+    /// cpu.max.burst in microseconds (None when unsupported or zero)
+    cgroup_cpu_burst_usec: Option<u64>,
+    sched_policy: String,
+    nice: i32,
+    /// Nice value of the process's session autogroup (`/proc/self/autogroup`);
+    /// None when autogrouping is disabled
+    autogroup_nice: Option<i32>,
+    rt_priority: Option<i32>,
+    frequency: cpufreq::CpuFrequencyInfo,
+    caches: Vec<cpucache::CpuCache>,
+    l3_total_bytes: Option<u64>,
+    isa_features: Vec<String>,
+    x86_64_level: Option<u8>,
+    core_groups: Vec<coretypes::CoreGroup>,
+    recommended_worker_count: usize,
+    steal_percent: Option<f64>,
+    /// Busy percentage per logical CPU over the sampling window, limited to
+    /// the cgroup's cpuset when one is set
+    per_cpu_busy: Vec<procstat::PerCpuBusy>,
+    throttling: Option<CpuThrottleStats>,
+    /// CPU PSI from the cgroup's `cpu.pressure`, else `/proc/pressure/cpu`.
+    /// `full` means every runnable task was waiting: total starvation rather
+    /// than a partial stall
+    pressure: Option<psi::PressureStats>,
+    usage: Option<CgroupCpuUsage>,
+    /// Thread-count environment variables that are set (unset ones are omitted)
+    env_threads: BTreeMap<String, String>,
+    /// Vulnerability mitigations that disable SMT or leave it exposed
+    mitigations: Vec<mitigations::Mitigation>,
+    /// `Cpus_allowed_list` from /proc/self/status, verbatim
+    cpus_allowed_list: Option<String>,
+    cpu_views: CpuViews,
+}
+
+/// The four ways of counting usable CPUs, side by side.
+#[derive(Serialize, JsonSchema, Clone, Debug)]
+struct CpuViews {
+    logical_cpus: usize,
+    affinity_cpus: Option<usize>,
+    cpuset_cpus: Option<usize>,
+    /// ceil(cgroup CPU quota)
+    quota_cpus: Option<usize>,
+    /// Which view is the binding minimum: "quota", "cpuset", "affinity" or "logical"
+    binding: String,
+}
+
+/// Lifetime CPU time consumed by the cgroup, in microseconds.
+#[derive(Serialize, JsonSchema, Clone, Copy)]
+struct CgroupCpuUsage {
+    /// Where the counters came from: "cpu.stat" (v2) or "cpuacct.usage" (v1)
+    source: &'static str,
+    usage_usec: u64,
+    user_usec: Option<u64>,
+    system_usec: Option<u64>,
+}
+
+/// Compressed swap cache usage and limits at the current cgroup.
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+struct ZswapInfo {
+    /// `memory.zswap.max`; None when "max" (unlimited)
+    limit_bytes: Option<u64>,
+    /// `memory.zswap.current`: compressed size of pages in zswap
+    current_bytes: Option<u64>,
+    /// `memory.zswap.writeback` (6.8+): whether pages may be written back
+    /// from zswap to the swap device; None on older kernels
+    writeback_enabled: Option<bool>,
+}
+
+/// Cumulative CFS throttling counters from `cpu.stat`.
+#[derive(Serialize, JsonSchema, Clone, Copy)]
+struct CpuThrottleStats {
+    nr_periods: u64,
+    nr_throttled: u64,
+    throttled_usec: u64,
+    /// Periods in which the cgroup ran into its `cpu.max.burst` allowance;
+    /// None on kernels without CPU burst (before 5.14)
+    nr_bursts: Option<u64>,
+    /// Total CPU time spent above the quota thanks to burst
+    burst_usec: Option<u64>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct DetailedMemoryInfo {
     system_total_bytes: u64,
+    /// `MemAvailable`: the kernel's estimate of how much can be allocated
+    /// without swapping, i.e. free RAM plus the page cache and slab it can
+    /// reclaim. Usually far more than `system_free_bytes`
     system_available_bytes: u64,
+    /// `MemFree`: RAM holding nothing at all (the "free" column of `free`).
+    /// Low values are normal; the kernel keeps otherwise idle RAM as cache
+    system_free_bytes: u64,
+    /// True when the kernel has no `MemAvailable` (before 3.14) and
+    /// `system_available_bytes` is estimated as MemFree + Buffers + Cached - Shmem
+    mem_available_estimated: bool,
     system_used_bytes: u64,
+    /// 0 on systems without swap
+    system_swap_total_bytes: u64,
+    system_swap_free_bytes: u64,
     cgroup_memory_limit_bytes: Option<u64>,
     cgroup_memory_usage_bytes: Option<u64>,
+    /// Swap limit at the current cgroup (memory.swap.max on v2; on v1 the
+    /// part of memory.memsw.limit_in_bytes above the RAM limit). 0 means
+    /// swap is disabled for the cgroup; None means no limit.
+    cgroup_swap_limit_bytes: Option<u64>,
+    cgroup_swap_usage_bytes: Option<u64>,
+    /// memory.high: reclaim throttling threshold (v2; None when "max")
+    memory_high_bytes: Option<u64>,
+    /// memory.low: best-effort protection from reclaim (v2)
+    memory_low_bytes: Option<u64>,
+    /// memory.min: hard protection from reclaim (v2)
+    memory_min_bytes: Option<u64>,
+    /// RAM-only ceiling: the smallest finite memory.max (v2) or
+    /// memory.limit_in_bytes (v1) on the current cgroup or any ancestor.
+    /// Swap is not included. None when no level sets a limit.
+    effective_memory_ceiling_bytes: Option<u64>,
+    /// RAM+swap ceiling: the RAM ceiling plus the smallest memory.swap.max in
+    /// the hierarchy (v2), or the smallest memory.memsw.limit_in_bytes (v1,
+    /// which already counts RAM+swap). None when swap is not capped.
+    effective_memory_swap_ceiling_bytes: Option<u64>,
+    usage_breakdown: Option<MemoryUsageBreakdown>,
+    /// Cgroup memory usage minus reclaimable file cache
+    effective_usage_bytes: Option<u64>,
+    /// File cache plus reclaimable slab, from memory.stat
+    reclaimable_bytes: Option<u64>,
+    /// Anonymous memory plus unreclaimable slab and kernel stacks
+    unreclaimable_bytes: Option<u64>,
+    /// Usage as a percentage of the hard limit, counting page cache; 0-100 with one decimal
+    usage_percent_of_limit: Option<f64>,
+    /// Unreclaimable memory as a percentage of the hard limit: the pressure
+    /// the OOM killer actually responds to
+    reclaim_adjusted_percent_of_limit: Option<f64>,
+    /// How much can still be allocated: min(limit - usage, MemAvailable)
+    effective_available_bytes: u64,
+    /// "hard" (memory.max), "soft" (only memory.high) or "none"
+    enforcement: String,
+    /// Non-reclaimable usage has crossed `--mem-warn-threshold` for the hard limit
+    memory_pressure_warning: bool,
+    /// Growth of `cgroup_memory_usage_bytes` over recent samples (--samples
+    /// only; negative when shrinking)
+    growth_bytes_per_sec: Option<f64>,
+    /// Extrapolated time until the hard limit is reached at that rate; only
+    /// when a limit is set and usage is growing
+    seconds_to_limit: Option<f64>,
+    /// Bytes left before the cgroup limit (limit minus usage), or system
+    /// available memory when no limit applies
+    memory_headroom_bytes: Option<u64>,
+    /// Headroom as a percentage of the limit (or of system total memory)
+    memory_headroom_percent: Option<f64>,
+    /// Huge page pools by page size; empty when the kernel exposes none
+    hugepages: Vec<hugepages::HugePagePool>,
+    /// Memory PSI from the cgroup's `memory.pressure`, else `/proc/pressure/memory`
+    pressure: Option<psi::PressureStats>,
+    /// `/dev/shm` (or `--shm-path`) size and usage; None when it does not exist
+    shm: Option<shm::ShmInfo>,
+    /// tmpfs mounts from `/proc/self/mountinfo`; the one holding `$TMPDIR`
+    /// (default /tmp) has `is_temp_dir` set
+    tmpfs_mounts: Vec<tmpfs::TmpfsMount>,
+    /// Transparent huge pages: the active choice in `transparent_hugepage/enabled`
+    /// and `/defrag`; None when the kernel was built without THP
+    thp_enabled: Option<String>,
+    thp_defrag: Option<String>,
+    /// `AnonHugePages` from `/proc/meminfo`: memory currently backed by THP
+    anon_huge_pages_bytes: Option<u64>,
+    kernel: KernelMemory,
+    page_cache: PageCache,
+    /// Base page size, `sysconf(_SC_PAGESIZE)`: 4 KiB on x86-64, often 64 KiB on ARM64
+    page_size_bytes: Option<u64>,
+    /// `Hugepagesize` from `/proc/meminfo`
+    default_hugepage_size_bytes: Option<u64>,
+    /// Kernel samepage merging; None when the kernel has no KSM
+    ksm: Option<ksm::KsmInfo>,
+    /// `vm.swappiness`
+    system_swappiness: Option<u64>,
+    /// v1 `memory.swappiness` at the current path; cgroup v2 has no per-cgroup swappiness
+    cgroup_swappiness: Option<u64>,
+    /// `vm.overcommit_*` policy and the `CommitLimit`/`Committed_AS` charge
+    overcommit: Option<overcommit::Overcommit>,
+    /// `memory.events`: counts for this cgroup and its descendants
+    events: Option<MemoryEvents>,
+    /// `memory.events.local`: counts for this cgroup only (v2)
+    events_local: Option<MemoryEvents>,
+    /// `memory.oom.group`: an OOM kill takes every process in the cgroup (v2)
+    oom_group: Option<bool>,
+    /// NUMA nodes the cgroup may allocate from (`cpuset.mems.effective`, or
+    /// v1 `cpuset.mems`); None on single-node machines
+    allowed_memory_nodes: Option<String>,
+    /// Total RAM on the allowed nodes
+    allowed_nodes_total_bytes: Option<u64>,
+    /// Memory node binding from `cpuset.mems.effective` (v1 `cpuset.mems`),
+    /// reported on single-node machines too
+    cpuset_mems: Option<Vec<u32>>,
+}
+
+/// Memory event counters. On cgroup v1 only `oom_kill` is available (from
+/// `memory.oom_control`, kernel 4.13+).
+#[derive(Serialize, JsonSchema)]
+struct MemoryEvents {
+    low: Option<u64>,
+    high: Option<u64>,
+    max: Option<u64>,
+    oom: Option<u64>,
+    oom_kill: Option<u64>,
+}
+
+/// Where the cgroup's memory goes, in bytes, from `memory.stat`. On cgroup v1
+/// `anon` and `file` come from `rss` and `cache`, `kernel` from
+/// `memory.kmem.usage_in_bytes`; fields a version does not track are None.
+#[derive(Serialize, JsonSchema)]
+struct MemoryUsageBreakdown {
+    anon: Option<u64>,
+    /// Page cache; mostly reclaimable under pressure
+    file: Option<u64>,
+    /// All kernel memory charged to the cgroup (stacks, slab, page tables, ...)
+    kernel: Option<u64>,
+    kernel_stack: Option<u64>,
+    slab: Option<u64>,
+    /// Dentries, inodes and other slab the kernel can free (v2)
+    slab_reclaimable: Option<u64>,
+    slab_unreclaimable: Option<u64>,
+    sock: Option<u64>,
+    shmem: Option<u64>,
+    swap: Option<u64>,
+}
+
+impl MemoryUsageBreakdown {
+    /// File cache plus reclaimable slab: what the kernel drops before it
+    /// resorts to the OOM killer.
+    fn reclaimable(&self) -> Option<u64> {
+        Some(self.file? + self.slab_reclaimable.unwrap_or(0))
+    }
+
+    /// Anonymous memory plus unreclaimable slab and kernel stacks: what the
+    /// cgroup cannot give back without swapping or being killed.
+    fn unreclaimable(&self) -> Option<u64> {
+        Some(self.anon? + self.slab_unreclaimable.unwrap_or(0) + self.kernel_stack.unwrap_or(0))
+    }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct DetailedCGroupInfo {
+    /// "v1", "v2" or "hybrid" (v1 controllers next to a cgroup2 mount)
     version: Option<String>,
+    /// The hierarchy handling each controller, "v1" or "v2"
+    controllers: BTreeMap<String, String>,
+    /// False when no cgroup filesystem is mounted anywhere, so
+    /// missing limits mean "unknown" rather than "none"
+    cgroup_mounted: bool,
     current_path: String,
+    /// How `current_path` was found: "cli" (`--cgroup-path`), "direct",
+    /// "suffix" or "mount-root" (host paths seen from a container without a
+    /// cgroup namespace), or "unresolved"
+    path_resolution: String,
+    /// Our path in each hierarchy from `/proc/self/cgroup`, keyed by
+    /// controller ("cpu", "memory", "name=systemd", ...; "unified" for v2)
+    paths_by_controller: BTreeMap<String, String>,
     cpu_quota: Option<f64>,
+    /// memory.max (v2) or memory.limit_in_bytes (v1) configured on the
+    /// current cgroup itself
     memory_limit_bytes: Option<u64>,
+    /// The smallest memory limit on the current cgroup or any ancestor
+    effective_memory_limit_bytes: Option<u64>,
+    /// The cgroup that sets `effective_memory_limit_bytes`, e.g. a parent slice
+    limiting_cgroup_path: Option<String>,
+    delegation_root: Option<String>,
+    memory_high_bytes: Option<u64>,
+    memory_low_bytes: Option<u64>,
+    memory_min_bytes: Option<u64>,
+    /// True when `cpu.idle` is set (e.g. systemd `CPUWeight=idle`); v2 only
+    cpu_idle: Option<bool>,
+    /// Processes in the current cgroup, including this one
+    cgroup_process_count: Option<usize>,
+    /// hugetlb controller limits and usage, per page size
+    hugetlb: Vec<hugepages::HugetlbLimit>,
+    /// Per-cgroup zswap settings (v2); None when the kernel has no `memory.zswap.*` files
+    zswap: Option<ZswapInfo>,
+    /// `cgroup.type`: "domain", "domain threaded", "threaded" or "domain invalid" (v2)
+    cgroup_type: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct DetailedReport {
     version: String,
+    /// When the report was taken, ISO-8601 UTC
+    timestamp: String,
     cpu: DetailedCpuInfo,
     memory: DetailedMemoryInfo,
     cgroup: DetailedCGroupInfo,
+    process: DetailedProcessInfo,
+    kernel_tunables: KernelTunables,
+    /// GPUs visible to this process (DRM sysfs and the NVIDIA driver's procfs)
+    gpus: Vec<gpu::GpuInfo>,
+    disks: Vec<diskstats::DiskStat>,
+    /// I/O PSI from the cgroup's `io.pressure`, else `/proc/pressure/io`
+    io_pressure: Option<psi::PressureStats>,
+    /// `--profile`, or the detected environment, that the warnings are tuned for
+    profile: profile::Profile,
+    warnings: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DetailedProcessInfo {
+    /// getrlimit for as, data, memlock, stack, nofile, nproc and core;
+    /// null soft/hard values are unlimited
+    rlimits: BTreeMap<String, rlimits::Rlimit>,
+    /// `/proc/self/oom_score`: the kernel's current kill ranking (higher dies first)
+    oom_score: Option<i64>,
+    /// `/proc/self/oom_score_adj`, -1000 (never kill) to 1000 (kill first)
+    oom_score_adj: Option<i64>,
+}
+
+/// Kernel memory from `/proc/meminfo`. It counts as "used" but belongs to no
+/// process, and only `SReclaimable` can be given back under pressure.
+#[derive(Serialize, JsonSchema, Debug, Default, PartialEq)]
+struct KernelMemory {
+    slab_bytes: Option<u64>,
+    slab_reclaimable_bytes: Option<u64>,
+    slab_unreclaimable_bytes: Option<u64>,
+    kernel_stack_bytes: Option<u64>,
+    page_tables_bytes: Option<u64>,
+}
+
+/// Page cache from `/proc/meminfo`. It is most of the gap between MemFree
+/// and MemAvailable, since clean cache can be dropped on demand.
+#[derive(Serialize, JsonSchema, Debug, Default, PartialEq)]
+struct PageCache {
+    buffers_bytes: Option<u64>,
+    cached_bytes: Option<u64>,
+    /// Modified pages not yet written back
+    dirty_bytes: Option<u64>,
+    /// Pages being written back right now
+    writeback_bytes: Option<u64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct KernelTunables {
+    /// `vm.max_map_count`: the per-process limit on memory mappings
+    max_map_count: Option<u64>,
+    /// Mappings this process currently has (lines in `/proc/self/maps`)
+    process_map_count: Option<u64>,
+}
+
+/// Whether CPU and memory are constrained, and by what. This is the single
+/// definition of "constrained" shared by every output mode.
+#[derive(Debug, Clone)]
+struct Constraints {
+    cpu: bool,
+    memory: bool,
+    /// "cpu.max quota", "cpuset", "affinity" or "cpu.idle"
+    cpu_reason: Option<&'static str>,
+    /// The cgroup memory limit, when it is below system memory
+    memory_limit_bytes: Option<u64>,
+    /// "max" (hard limit) or "high" (only memory.high is set)
+    memory_limit_kind: Option<&'static str>,
+}
+
+impl Constraints {
+    /// `memory.max` wins over `memory.high`; a high-only cgroup is still
+    /// constrained because the kernel throttles it above that value.
+    fn new(
+        cpu_reason: Option<&'static str>,
+        cgroup_memory_limit: Option<u64>,
+        memory_high: Option<u64>,
+        system_total: u64,
+    ) -> Self {
+        let limit = match (cgroup_memory_limit, memory_high) {
+            (Some(max), _) => Some((max, "max")),
+            (None, Some(high)) => Some((high, "high")),
+            (None, None) => None,
+        }
+        .filter(|&(limit, _)| limit < system_total);
+        Constraints {
+            cpu: cpu_reason.is_some(),
+            memory: limit.is_some(),
+            cpu_reason,
+            memory_limit_bytes: limit.map(|(bytes, _)| bytes),
+            memory_limit_kind: limit.map(|(_, kind)| kind),
+        }
+    }
+
+    fn any(&self) -> bool {
+        self.cpu || self.memory
+    }
+
+    /// "hard" when memory.max caps the cgroup, "soft" when only memory.high
+    /// throttles it, "none" otherwise.
+    fn memory_enforcement(&self) -> &'static str {
+        match self.memory_limit_kind {
+            Some("max") => "hard",
+            Some(_) => "soft",
+            None => "none",
+        }
+    }
+
+    /// e.g. "hard limit (OOM at 512 MiB)" or "soft limit (throttle at 512 MiB, no hard cap)"
+    fn describe_memory_enforcement(&self, units: Units) -> Option<String> {
+        let limit = format_bytes(self.memory_limit_bytes?, units);
+        Some(match self.memory_enforcement() {
+            "hard" => format!("hard limit (OOM at {})", limit),
+            _ => format!("soft limit (throttle at {}, no hard cap)", limit),
+        })
+    }
+}
+
+impl DetailedReport {
+    fn constraints(&self) -> Constraints {
+        let cpu_reason = cpu_constraint_reason(
+            self.cpu.system_logical_cpus,
+            self.cpu.cgroup_cpu_quota,
+            self.cpu.cpu_views.cpuset_cpus,
+            self.cpu.cpu_views.affinity_cpus,
+            self.cgroup.cpu_idle == Some(true),
+        );
+        Constraints::new(
+            cpu_reason,
+            self.memory.cgroup_memory_limit_bytes,
+            self.memory.memory_high_bytes,
+            self.memory.system_total_bytes,
+        )
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
+    if cli.strict || cli.verbose > 0 {
+        probelog::enable();
+    }
+
+    if cli.json_schema {
+        println!("{}", serde_json::to_string_pretty(&report_json_schema()).unwrap());
+        return;
+    }
+
+    if cli.diff_live {
+        run_diff_live(&cli);
+        return;
+    }
+    if cli.watch {
+        run_watch(&cli);
+        return;
+    }
+    if let Some(count) = cli.samples {
+        run_samples(&cli, count);
+        return;
+    }
+
+    if let Some(paths) = &cli.compare_cgroup {
+        print_cgroup_comparison(&cli, &paths[0], &paths[1]);
+        return;
+    }
 
     // Gather data once
-    let system_logical_cpus = get_system_cpu_count();
-    let system_physical_cpus = get_system_physical_cpu_count();
+    let cgroup_path = target_cgroup_path(&cli);
+    if cli.raw {
+        print!("{}", rawdump::dump(&cgroup_path));
+        return;
+    }
+    if let Some(count) = cli.top_consumers {
+        print_top_consumers(&cli, &cgroup_path, count);
+        return;
+    }
+    let sections = gather::gather_sections(&cgroup_path);
+    if cli.strict {
+        let errors = sections.errors();
+        for (section, err) in &errors {
+            eprintln!("systemcheck: {}: {}", section, err);
+        }
+        if !errors.is_empty() {
+            std::process::exit(1);
+        }
+    }
+
+    // Without --strict, a section that failed falls back to the lenient readers
+    let system_logical_cpus = sections.cpu.as_ref().map(|cpu| cpu.present).unwrap_or_else(|_| get_system_cpu_count());
     let available_cpus = num_cpus::get();
-    let cgroup_path = get_current_cgroup_path();
-    let cgroup_cpu_quota = get_cgroup_cpu_quota_for_path(&cgroup_path);
-    let (system_total, system_available) = get_system_memory_from_proc();
-    let system_used = system_total.saturating_sub(system_available);
-    let cgroup_memory_limit = get_cgroup_memory_limit_for_path(&cgroup_path);
-    let cgroup_memory_usage = get_cgroup_memory_usage_for_path(&cgroup_path);
-
-    let cgroup_v2 = Path::new("/sys/fs/cgroup/cgroup.controllers").exists();
-    let cgroup_v1 = Path::new("/sys/fs/cgroup/cpu").exists()
-        || Path::new("/sys/fs/cgroup/memory").exists();
-    let cgroup_version = if cgroup_v2 {
-        Some("v2".to_string())
-    } else if cgroup_v1 {
-        Some("v1".to_string())
-    } else {
-        None
-    };
+    let exact_limits = sections.cgroup.as_ref().ok();
+    // Limits set on the cgroup itself, else inherited ones (unless --no-fallback-to-root)
+    let cgroup_cpu_quota = exact_limits
+        .and_then(|limits| limits.cpu_quota)
+        .or_else(|| cpu_quota_for_cli(&cli, &cgroup_path));
+    let (system_total, system_available) = sections
+        .memory
+        .as_ref()
+        .map(|memory| (memory.total_bytes, memory.available_bytes))
+        .unwrap_or_else(|_| get_system_memory_from_proc());
+    let cgroup_memory_limit = [exact_limits.and_then(|limits| limits.memory_limit_bytes), memory_limit_for_cli(&cli, &cgroup_path)]
+        .into_iter()
+        .flatten()
+        .min();
+    let cpu_idle = get_cgroup_cpu_idle_for_path(&cgroup_path) == Some(true);
+    let cpuset_cpus = get_cgroup_cpuset_count_for_path(&cgroup_path);
+    let affinity_cpus = get_process_affinity_count();
+    let constraints = Constraints::new(
+        cpu_constraint_reason(system_logical_cpus, cgroup_cpu_quota, cpuset_cpus, affinity_cpus, cpu_idle),
+        cgroup_memory_limit,
+        read_cgroup_v2_bytes(&cgroup_path, "memory.high"),
+        system_total,
+    );
 
-    if cli.json {
-        if cli.verbose {
-            let report = DetailedReport {
-                version: VERSION.to_string(),
-                cpu: DetailedCpuInfo {
-                    system_logical_cpus,
-                    system_physical_cpus,
-                    available_cpus,
-                    cgroup_cpu_quota,
-                },
-                memory: DetailedMemoryInfo {
-                    system_total_bytes: system_total,
-                    system_available_bytes: system_available,
-                    system_used_bytes: system_used,
-                    cgroup_memory_limit_bytes: cgroup_memory_limit,
-                    cgroup_memory_usage_bytes: cgroup_memory_usage,
-                },
-                cgroup: DetailedCGroupInfo {
-                    version: cgroup_version,
-                    current_path: cgroup_path.clone(),
-                    cpu_quota: cgroup_cpu_quota,
-                    memory_limit_bytes: cgroup_memory_limit,
-                },
-            };
-            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    // With --gate, check failures are collected and the report is still printed
+    let mut gate_failures = Vec::new();
+
+    if cli.fail_if_unconstrained && cgroup_cpu_quota.is_none() && cgroup_memory_limit.is_none() {
+        let location = if cgroup_path.is_empty() { "/" } else { cgroup_path.as_str() };
+        let failure = format!("no CPU quota or memory limit found for cgroup {}", location);
+        if cli.gate {
+            gate_failures.push(failure);
+        } else {
+            eprintln!("systemcheck: {}", failure);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(config) = &cli.check_config {
+        let mismatches = check_config_mismatches(
+            config,
+            cgroup_cpu_quota,
+            cgroup_memory_limit,
+            get_cgroup_pids_limit_for_path(&cgroup_path),
+            cli.assert_cpus_tolerance,
+            cli.units,
+        );
+        if cli.gate {
+            gate_failures.extend(mismatches.iter().map(|(key, expected, found)| {
+                format!("{} is {}, {} expects {}", key, found, config.path, expected)
+            }));
+        } else if mismatches.is_empty() {
+            let message = format!("systemcheck: limits match {}", config.path);
+            if cli.output_format() != OutputFormat::Text {
+                eprintln!("{}", message);
+            } else {
+                println!("{}", message);
+            }
+            if cli.assert_cpus.is_none() && cli.min_cpus.is_none() && cli.min_memory.is_none() {
+                return;
+            }
+        } else {
+            eprintln!("--- {} (expected)", config.path);
+            eprintln!("+++ running environment");
+            for (key, expected, found) in &mismatches {
+                eprintln!("-{} = {}", key, expected);
+                eprintln!("+{} = {}", key, found);
+            }
+            std::process::exit(1);
+        }
+    }
+
+    if cli.gate {
+        if let Some(expected) = cli.assert_cpus {
+            gate_failures.extend(assert_cpus_failure(expected, cli.assert_cpus_tolerance, cgroup_cpu_quota));
+        }
+        if cli.min_cpus.is_some() || cli.min_memory.is_some() {
+            let (effective_cpus, memory_ceiling) = preflight_resources(
+                &cgroup_path,
+                system_logical_cpus,
+                [cgroup_cpu_quota, cpuset_cpus.map(|c| c as f64), affinity_cpus.map(|c| c as f64)],
+                [cgroup_memory_limit, constraints.memory_limit_bytes],
+                system_total,
+            );
+            gate_failures.extend(preflight_failures(&cli, effective_cpus, memory_ceiling));
+        }
+        if memory_pressure_warning(cli.mem_warn_threshold, &cgroup_path, get_cgroup_memory_usage_for_path(&cgroup_path), cgroup_memory_limit) {
+            gate_failures.push("memory usage is past --mem-warn-threshold".to_string());
+        }
+    } else if let Some(expected) = cli.assert_cpus {
+        match assert_cpus_failure(expected, cli.assert_cpus_tolerance, cgroup_cpu_quota) {
+            None => {
+                let message = format!("systemcheck: CPU quota ok ({:.2} CPUs)", expected);
+                if cli.output_format() != OutputFormat::Text {
+                    eprintln!("{}", message);
+                } else {
+                    println!("{}", message);
+                }
+            }
+            Some(failure) => {
+                eprintln!("systemcheck: assertion failed: {}", failure);
+                std::process::exit(1);
+            }
+        }
+        if cli.min_cpus.is_none() && cli.min_memory.is_none() {
+            return;
+        }
+    }
+
+    if !cli.gate && (cli.min_cpus.is_some() || cli.min_memory.is_some()) {
+        let (effective_cpus, memory_ceiling) = preflight_resources(
+            &cgroup_path,
+            system_logical_cpus,
+            [cgroup_cpu_quota, cpuset_cpus.map(|c| c as f64), affinity_cpus.map(|c| c as f64)],
+            [cgroup_memory_limit, constraints.memory_limit_bytes],
+            system_total,
+        );
+        let failures = preflight_failures(&cli, effective_cpus, memory_ceiling);
+        if failures.is_empty() {
+            let message = format!(
+                "systemcheck: preflight ok ({:.2} CPUs, {} memory)",
+                effective_cpus,
+                format_bytes(memory_ceiling, cli.units)
+            );
+            // Machine-readable formats keep stdout free of anything else
+            if cli.output_format() != OutputFormat::Text {
+                eprintln!("{}", message);
+            } else {
+                println!("{}", message);
+            }
+            return;
+        }
+        for failure in failures {
+            eprintln!("systemcheck: preflight failed: {}", failure);
+        }
+        std::process::exit(1);
+    }
+
+    if cli.bench_env {
+        print!("{}", bench_env_block(&cgroup_path, available_cpus, system_logical_cpus, cgroup_cpu_quota, cgroup_memory_limit, system_total));
+    } else if cli.summary {
+        println!(
+            "{}",
+            summary_line(
+                available_cpus,
+                system_logical_cpus,
+                cgroup_cpu_quota,
+                &constraints,
+                system_total,
+                &cgroup_path,
+                cli.units,
+            )
+        );
+    } else if cli.output_format() != OutputFormat::Text {
+        let value = if cli.verbose > 0 {
+            serde_json::to_value(gather_detailed_report(&cli))
         } else {
-            let constrained_cpu = available_cpus < system_logical_cpus;
-            let constrained_mem = cgroup_memory_limit
-                .map(|lim| lim < system_total)
-                .unwrap_or(false);
+            let cgroup_memory_usage = get_cgroup_memory_usage_for_path(&cgroup_path);
+            let pressure_warning = memory_pressure_warning(cli.mem_warn_threshold, &cgroup_path, cgroup_memory_usage, cgroup_memory_limit);
+            let simple_memory_limit = cgroup_memory_limit.or(constraints.memory_limit_bytes);
             let report = SimpleReport {
                 version: VERSION.to_string(),
+                timestamp: timestamp::now_iso8601(),
                 cpu: SimpleCpuSummary {
                     available_cpus,
                     system_logical_cpus,
-                    constrained: constrained_cpu,
+                    constrained: constraints.cpu,
+                    sub_core_quota: is_sub_core_quota(cgroup_cpu_quota),
+                    constraint_reason: constraints.cpu_reason.map(str::to_string),
                 },
                 memory: SimpleMemorySummary {
                     system_available_bytes: system_available,
-                    cgroup_memory_limit_bytes: cgroup_memory_limit,
-                    constrained: constrained_mem,
+                    cgroup_memory_limit_bytes: simple_memory_limit,
+                    effective_available_bytes: effective_available_memory(
+                        constraints.memory_limit_bytes,
+                        cgroup_memory_usage,
+                        system_available,
+                    ),
+                    cgroup_memory_usage_bytes: cgroup_memory_usage,
+                    usage_percent_of_limit: usage_percent_of_limit(cgroup_memory_usage, simple_memory_limit),
+                    constrained: constraints.memory,
+                    limit_kind: constraints.memory_limit_kind.map(str::to_string),
+                    enforcement: constraints.memory_enforcement().to_string(),
+                    memory_pressure_warning: pressure_warning,
                 },
             };
-            println!("{}", serde_json::to_string_pretty(&report).unwrap());
-        }
-        return;
-    }
-
-    if cli.verbose {
+            serde_json::to_value(report)
+        };
+        print!("{}", render_report(&value.unwrap(), cli.output_format()));
+    } else if cli.verbose > 0 {
         // Verbose, current-style sections
-        println!("systemcheck v{}\n", VERSION);
+        println!("systemcheck v{} ({})\n", VERSION, timestamp::now_iso8601());
         println!("=== System Check - Resource Diagnostics ===\n");
-        print_cpu_info();
+        print_cpu_info(&cli);
         println!();
-        print_memory_info();
+        print_memory_info(&cli);
         println!();
-        print_cgroup_info();
-        return;
+        print_cgroup_info(&cli);
+        println!();
+        print_process_info(&cli);
+        println!();
+        print_gpu_info();
+        println!();
+        print_disk_info(&cli);
+    } else {
+        // Simple summary output
+        println!("systemcheck: {} ({})\n", VERSION, timestamp::now_iso8601());
+        println!("CPU Usage:");
+        let assigned_cpus = [cpuset_cpus, affinity_cpus].into_iter().flatten().min().unwrap_or(available_cpus);
+        match simple_cpu_line(constraints.cpu_reason, cgroup_cpu_quota, assigned_cpus, system_logical_cpus) {
+            Some(line) => println!("{}", line),
+            None if constraints.cpu => {}
+            None => println!("Not constrained: {} CPUs available", available_cpus),
+        }
+        if let Some(quota) = cgroup_cpu_quota
+            && is_sub_core_quota(cgroup_cpu_quota)
+        {
+            println!("{}", sub_core_quota_warning(quota));
+        }
+        if cpu_idle {
+            println!("{}", CPU_IDLE_WARNING);
+        }
+        println!();
+
+        // Memory summary line
+        if let Some(high) = constraints.memory_limit_bytes
+            && constraints.memory_limit_kind == Some("high")
+        {
+            println!(
+                "Memory: Throttled above {} (memory.high soft limit, no hard cap) of {} available",
+                format_bytes(high, cli.units),
+                format_bytes(system_available, cli.units)
+            );
+        } else if let Some(limit) = constraints.memory_limit_bytes {
+            let usage = get_cgroup_memory_usage_for_path(&cgroup_path);
+            let usable = effective_available_memory(Some(limit), usage, system_available);
+            match usage {
+                Some(usage) => println!(
+                    "Memory: {} usable (limit {}, {} used by this cgroup, {:.0}% used)",
+                    format_bytes(usable, cli.units),
+                    format_bytes(limit, cli.units),
+                    format_bytes(usage, cli.units),
+                    usage_percent_of_limit(Some(usage), Some(limit)).unwrap_or(0.0)
+                ),
+                None => println!("Memory: {} usable (limit {})", format_bytes(usable, cli.units), format_bytes(limit, cli.units)),
+            }
+        } else {
+            println!(
+                "Memory: Unconstrained, {} available",
+                format_bytes(system_available, cli.units)
+            );
+        }
+        if constraints.memory_limit_bytes.is_some()
+            && let Some((headroom, percent)) = memory_headroom(
+                constraints.memory_limit_bytes,
+                get_cgroup_memory_usage_for_path(&cgroup_path),
+                system_total,
+                system_available,
+            )
+        {
+            println!("Headroom: {} ({:.1}% of limit) left before OOM", format_bytes(headroom, cli.units), percent);
+        }
+        if memory_pressure_warning(
+            cli.mem_warn_threshold,
+            &cgroup_path,
+            get_cgroup_memory_usage_for_path(&cgroup_path),
+            cgroup_memory_limit,
+        ) {
+            println!("{}", emphasize("⚠️  Memory usage is near the cgroup limit; the OOM killer is close"));
+        }
+
+        // Swap decides whether low memory means paging or the OOM killer
+        if system_total > 0 && (system_available as f64) < system_total as f64 * LOW_MEMORY_FRACTION {
+            let (swap_total, _) = get_system_swap_from_proc();
+            if swap_total > 0 {
+                println!("{} (memory is low; expect paging before OOM kills)", swap_line(cli.units));
+            }
+        }
+
+        // CGroup summary note
+        let looks_default_user = is_default_user_slice_path(&cgroup_path);
+        let explicit_limits = has_explicit_limits_at_path(&cgroup_path);
+        if !is_cgroupfs_mounted() {
+            println!("CGroup: unavailable (not mounted); limits could not be read");
+        } else if cli.profile() == profile::Profile::Container && cgroup_cpu_quota.is_none() && cgroup_memory_limit.is_none() {
+            println!("⚠️  {}", CONTAINER_WITHOUT_LIMITS_WARNING);
+        } else if looks_default_user && !explicit_limits {
+            println!("CGroup: default user slice (no explicit limits)");
+        } else if !cgroup_path.is_empty() && cgroup_path != "/" {
+            if explicit_limits {
+                println!("CGroup: limits present at {}", cgroup_path);
+            } else {
+                println!("CGroup: {} (no explicit limits)", cgroup_path);
+            }
+        }
+        println!("\nsee more details with systemcheck -v");
     }
 
-    // Simple summary output
-    println!("systemcheck: {}\n", VERSION);
-    println!("CPU Usage:");
-    if available_cpus < system_logical_cpus {
-        println!("Constrained to {} of {} CPUs", available_cpus, system_logical_cpus);
-    } else {
-        println!("Not constrained: {} CPUs available", available_cpus);
+    if cli.gate && !gate_failures.is_empty() {
+        for failure in &gate_failures {
+            eprintln!("systemcheck: gate failed: {}", failure);
+        }
+        std::process::exit(1);
     }
-    println!();
+}
 
-    // Memory summary line
-    if let Some(limit) = cgroup_memory_limit {
-        println!(
-            "Memory: Limited to {} of {} available",
-            humanize_bytes_binary!(limit),
-            humanize_bytes_binary!(system_available)
-        );
-    } else {
-        println!(
-            "Memory: Unconstrained, {} available",
-            humanize_bytes_binary!(system_available)
-        );
+const OOM_GROUP_KILL_WARNING: &str =
+    "OOM kills occurred with memory.oom.group=1: each one killed every process in the cgroup, not just the one that exceeded the limit";
+
+/// In the container profile, no limit at all is worth a warning; on bare
+/// metal or in a VM the plain "no explicit limits" note is enough.
+const CONTAINER_WITHOUT_LIMITS_WARNING: &str =
+    "running in a container with no CPU quota or memory limit; the runtime was probably started without --cpus/--memory, so this job can use the whole host";
+
+const CGROUP_UNMOUNTED_WARNING: &str =
+    "no cgroup filesystem is mounted, so limits cannot be read; \"unconstrained\" may be wrong";
+
+/// Whether any cgroup (v1) or cgroup2 filesystem is mounted, wherever it
+/// lives. Falls back to looking for the hierarchy when mountinfo is
+/// unreadable.
+fn is_cgroupfs_mounted() -> bool {
+    match cgroupfs::discover() {
+        Some(mounts) => !mounts.is_empty(),
+        None => detect_cgroup_version().is_some(),
+    }
+}
+
+fn detect_cgroup_version() -> Option<String> {
+    cgroupfs::mounts().version().map(str::to_string)
+}
+
+fn gather_detailed_report(cli: &Cli) -> DetailedReport {
+    let timestamp = timestamp::now_iso8601();
+    let system_logical_cpus = get_system_cpu_count();
+    let system_physical_cpus = get_system_physical_cpu_count();
+    let available_cpus = num_cpus::get();
+    let cgroup_path = target_cgroup_path(cli);
+    let cgroup_cpu_quota = cpu_quota_for_cli(cli, &cgroup_path);
+    let (system_total, system_available) = get_system_memory_from_proc();
+    let system_used = system_total.saturating_sub(system_available);
+    let (system_swap_total, system_swap_free) = get_system_swap_from_proc();
+    let cgroup_memory_limit = get_cgroup_memory_limit_for_path(&cgroup_path);
+    let effective_memory_limit = effective_memory_limit_for_path(&cgroup_path);
+    let node_restriction = numa::read_node_restriction(&cgroup_path);
+    let cpuset_mems = numa::read_cpuset_mems(&cgroup_path);
+    let cgroup_memory_usage = get_cgroup_memory_usage_stable(&cgroup_path, cli.stable_reads);
+    let usage_breakdown = get_cgroup_memory_breakdown_for_path(&cgroup_path);
+    let memory_constraints = Constraints::new(
+        None,
+        memory_limit_for_cli(cli, &cgroup_path),
+        read_cgroup_v2_bytes(&cgroup_path, "memory.high"),
+        system_total,
+    );
+    let memory_limit = memory_constraints.memory_limit_bytes;
+    let headroom = memory_headroom(memory_limit, cgroup_memory_usage, system_total, system_available);
+    let hard_memory_limit = memory_limit_for_cli(cli, &cgroup_path);
+    let pressure_warning = memory_pressure_warning(cli.mem_warn_threshold, &cgroup_path, cgroup_memory_usage, hard_memory_limit);
+    let sched = get_process_sched_info();
+    let cache_info = cpucache::read_cpu_caches();
+    let isa_info = isa::read_isa_info();
+    let core_groups = coretypes::read_core_groups();
+    let recommended_workers =
+        recommended_worker_count(available_cpus, cgroup_cpu_quota, &core_groups, cli.perf_cores_only);
+    let env_threads = read_thread_env_vars();
+    let cpuset = get_cgroup_cpuset_for_path(&cgroup_path);
+    let cpu_sample = procstat::sample_proc_stat(steal_sample_window(cli));
+    let mut warnings = thread_env_warnings(&env_threads, recommended_workers, cgroup_cpu_quota, available_cpus);
+    let profile = cli.profile();
+    if profile == profile::Profile::Container && cgroup_cpu_quota.is_none() && hard_memory_limit.is_none() {
+        warnings.push(CONTAINER_WITHOUT_LIMITS_WARNING.to_string());
+    }
+    if pressure_warning {
+        warnings.push("memory usage is near the cgroup limit (--mem-warn-threshold); the OOM killer is close".to_string());
     }
+    warnings.extend(quota_cpuset_mismatch_warning(cgroup_cpu_quota, get_cgroup_cpuset_count_for_path(&cgroup_path)));
+    let gpus = gpu::read_gpus();
+    if gpus.is_empty() && gpu::nvidia_device_nodes_present() {
+        warnings.push(HIDDEN_GPU_NOTE.to_string());
+    }
+    let kernel_tunables = read_kernel_tunables();
+    warnings.extend(map_count_warning(&kernel_tunables));
+    let process_rlimits = rlimits::read_rlimits();
+    warnings.extend(rlimit_warnings(&process_rlimits, memory_limit, cli.units));
+    let cgroup_mounted = is_cgroupfs_mounted();
+    if !cgroup_mounted {
+        warnings.push(CGROUP_UNMOUNTED_WARNING.to_string());
+    }
+    let cgroup_type = read_cgroup_v2_file(&cgroup_path, "cgroup.type");
+    if cgroup_type.as_deref() == Some("domain invalid") {
+        warnings.push(DOMAIN_INVALID_WARNING.to_string());
+    }
+    let overcommit_info = overcommit::read_overcommit();
+    if let Some(warning) = overcommit_info.as_ref().and_then(commit_limit_warning) {
+        warnings.push(warning);
+    }
+    let page_cache = read_page_cache();
+    warnings.extend(dirty_pages_warning(&page_cache, system_total, cli.units));
+    let shm_info = shm::read_shm(&cli.shm_path);
+    if let Some(shm_info) = &shm_info {
+        warnings.extend(shm::shm_warnings(shm_info, available_cpus, memory_limit));
+    }
+    let io_pressure = psi::read_pressure("io", &cgroup_path);
+    let events = get_cgroup_memory_events_for_path(&cgroup_path, "memory.events");
+    if events.as_ref().and_then(|events| events.oom_kill).is_some_and(|kills| kills > 0)
+        && get_cgroup_oom_group_for_path(&cgroup_path) == Some(true)
+    {
+        warnings.push(OOM_GROUP_KILL_WARNING.to_string());
+    }
+    if let (Some(cpus), Some(mems)) = (cpuset.as_deref(), cpuset_mems.as_deref()) {
+        warnings.extend(numa::binding_mismatch_warning(&numa::nodes_of_cpus(cpus), mems));
+    }
+    let tmpfs_mounts = tmpfs::read_tmpfs_mounts();
+    warnings.extend(tmpfs::tmpfs_warning(&tmpfs_mounts, hard_memory_limit));
+
+    DetailedReport {
+        version: VERSION.to_string(),
+        timestamp,
+        cpu: DetailedCpuInfo {
+            system_logical_cpus,
+            system_online_cpus: get_system_online_cpu_count(),
+            system_physical_cpus,
+            available_cpus,
+            cgroup_cpu_quota,
+            cgroup_cpu_burst_usec: get_cgroup_cpu_burst_for_path(&cgroup_path),
+            sched_policy: sched.policy,
+            nice: sched.nice,
+            autogroup_nice: sched.autogroup_nice,
+            rt_priority: sched.rt_priority,
+            frequency: cpufreq::read_cpu_frequency_info(),
+            caches: cache_info.caches,
+            l3_total_bytes: cache_info.l3_total_bytes,
+            isa_features: isa_info.features,
+            x86_64_level: isa_info.x86_64_level,
+            core_groups,
+            recommended_worker_count: recommended_workers,
+            steal_percent: cpu_sample.as_ref().and_then(|(before, after)| procstat::aggregate_steal_percent(before, after)),
+            per_cpu_busy: cpu_sample
+                .as_ref()
+                .map(|(before, after)| procstat::per_cpu_busy(before, after, cpuset.as_deref()))
+                .unwrap_or_default(),
+            throttling: get_cgroup_cpu_throttling_for_path(&cgroup_path),
+            pressure: psi::read_pressure("cpu", &cgroup_path),
+            usage: get_cgroup_cpu_usage_for_path(&cgroup_path),
+            env_threads,
+            mitigations: mitigations::read_capacity_mitigations(),
+            cpus_allowed_list: read_cpus_allowed_list(),
+            cpu_views: get_cpu_views(system_logical_cpus, cgroup_cpu_quota, &cgroup_path),
+        },
+        memory: DetailedMemoryInfo {
+            system_total_bytes: system_total,
+            system_available_bytes: system_available,
+            system_free_bytes: read_meminfo_value("MemFree").unwrap_or(0),
+            mem_available_estimated: mem_available_estimated(),
+            system_used_bytes: system_used,
+            system_swap_total_bytes: system_swap_total,
+            system_swap_free_bytes: system_swap_free,
+            cgroup_memory_limit_bytes: cgroup_memory_limit,
+            cgroup_memory_usage_bytes: cgroup_memory_usage,
+            cgroup_swap_limit_bytes: get_cgroup_swap_limit_for_path(&cgroup_path),
+            cgroup_swap_usage_bytes: get_cgroup_swap_usage_for_path(&cgroup_path),
+            memory_high_bytes: read_cgroup_v2_bytes(&cgroup_path, "memory.high"),
+            memory_low_bytes: read_cgroup_v2_bytes(&cgroup_path, "memory.low"),
+            memory_min_bytes: read_cgroup_v2_bytes(&cgroup_path, "memory.min"),
+            effective_memory_ceiling_bytes: get_effective_memory_ceiling_for_path(&cgroup_path),
+            effective_memory_swap_ceiling_bytes: get_effective_memory_swap_ceiling_for_path(&cgroup_path),
+            effective_usage_bytes: effective_memory_usage(cgroup_memory_usage, usage_breakdown.as_ref()),
+            reclaimable_bytes: usage_breakdown.as_ref().and_then(MemoryUsageBreakdown::reclaimable),
+            unreclaimable_bytes: usage_breakdown.as_ref().and_then(MemoryUsageBreakdown::unreclaimable),
+            usage_percent_of_limit: usage_percent_of_limit(cgroup_memory_usage, memory_limit),
+            reclaim_adjusted_percent_of_limit: usage_percent_of_limit(
+                pressure_memory_usage(cgroup_memory_usage, usage_breakdown.as_ref()),
+                memory_limit,
+            ),
+            effective_available_bytes: effective_available_memory(memory_limit, cgroup_memory_usage, system_available),
+            enforcement: memory_constraints.memory_enforcement().to_string(),
+            memory_pressure_warning: pressure_warning,
+            growth_bytes_per_sec: None,
+            seconds_to_limit: None,
+            memory_headroom_bytes: headroom.map(|(bytes, _)| bytes),
+            memory_headroom_percent: headroom.map(|(_, percent)| percent),
+            pressure: psi::read_pressure("memory", &cgroup_path),
+            shm: shm_info,
+            tmpfs_mounts,
+            overcommit: overcommit_info,
+            thp_enabled: read_sysfs_choice(THP_ENABLED),
+            thp_defrag: read_sysfs_choice(THP_DEFRAG),
+            anon_huge_pages_bytes: read_meminfo_value("AnonHugePages"),
+            kernel: read_kernel_memory(),
+            page_cache,
+            page_size_bytes: get_page_size(),
+            default_hugepage_size_bytes: read_meminfo_value("Hugepagesize"),
+            ksm: get_page_size().and_then(ksm::read_ksm),
+            system_swappiness: read_trimmed("/proc/sys/vm/swappiness").and_then(|value| value.parse().ok()),
+            cgroup_swappiness: get_cgroup_swappiness_for_path(&cgroup_path),
+            hugepages: hugepages::read_hugepage_pools(),
+            usage_breakdown,
+            events,
+            events_local: get_cgroup_memory_events_for_path(&cgroup_path, "memory.events.local"),
+            oom_group: get_cgroup_oom_group_for_path(&cgroup_path),
+            allowed_memory_nodes: node_restriction.as_ref().map(|nodes| nodes.allowed_nodes.clone()),
+            allowed_nodes_total_bytes: node_restriction.map(|nodes| nodes.allowed_total_bytes),
+            cpuset_mems,
+        },
+        cgroup: DetailedCGroupInfo {
+            version: detect_cgroup_version(),
+            controllers: cgroupfs::mounts().controller_versions(),
+            cgroup_mounted,
+            cpu_quota: cgroup_cpu_quota,
+            memory_limit_bytes: cgroup_memory_limit,
+            effective_memory_limit_bytes: effective_memory_limit.as_ref().map(|(bytes, _)| *bytes),
+            limiting_cgroup_path: effective_memory_limit.map(|(_, path)| path),
+            delegation_root: get_cgroup_delegation_root(&cgroup_path),
+            memory_high_bytes: read_cgroup_v2_bytes(&cgroup_path, "memory.high"),
+            memory_low_bytes: read_cgroup_v2_bytes(&cgroup_path, "memory.low"),
+            memory_min_bytes: read_cgroup_v2_bytes(&cgroup_path, "memory.min"),
+            cpu_idle: get_cgroup_cpu_idle_for_path(&cgroup_path),
+            cgroup_process_count: get_cgroup_process_count_for_path(&cgroup_path),
+            hugetlb: hugepages::read_hugetlb_limits(&cgroup_path),
+            zswap: read_cgroup_zswap(&cgroup_path),
+            cgroup_type,
+            current_path: cgroup_path,
+            path_resolution: cgroup_path_resolution(cli),
+            paths_by_controller: read_controller_paths(),
+        },
+        process: DetailedProcessInfo {
+            rlimits: process_rlimits,
+            oom_score: read_trimmed("/proc/self/oom_score").and_then(|value| value.parse().ok()),
+            oom_score_adj: read_trimmed("/proc/self/oom_score_adj").and_then(|value| value.parse().ok()),
+        },
+        kernel_tunables,
+        gpus,
+        disks: diskstats::read_diskstats(),
+        io_pressure,
+        profile,
+        warnings,
+    }
+}
+
+/// Takes `count` detailed reports `--interval` apart. With `--ndjson` each
+/// sample is written and flushed as its own line as soon as it is gathered;
+/// otherwise all samples are printed at the end as one JSON array.
+fn run_samples(cli: &Cli, count: u32) {
+    use std::io::Write;
+
+    let interval = std::time::Duration::from_secs_f64(cli.interval);
+    let mut collected = Vec::new();
+    let mut trend = memtrend::MemoryTrend::default();
+    for i in 0..count {
+        if i > 0 {
+            std::thread::sleep(interval);
+        }
+        let mut sample = gather_detailed_report(cli);
+        if let Some(usage) = sample.memory.cgroup_memory_usage_bytes {
+            trend.push(std::time::Instant::now(), usage);
+            sample.memory.growth_bytes_per_sec = trend.bytes_per_sec();
+            let limit = memory_limit_for_cli(cli, &target_cgroup_path(cli));
+            sample.memory.seconds_to_limit = sample
+                .memory
+                .growth_bytes_per_sec
+                .zip(limit)
+                .and_then(|(rate, limit)| memtrend::seconds_to_limit(rate, usage, limit));
+        }
+        if cli.ndjson {
+            let mut stdout = std::io::stdout().lock();
+            let _ = writeln!(stdout, "{}", serde_json::to_string(&sample).unwrap());
+            let _ = stdout.flush();
+        } else {
+            collected.push(sample);
+        }
+    }
+    if !cli.ndjson {
+        println!("{}", serde_json::to_string_pretty(&collected).unwrap());
+    }
+}
+
+/// Prints one compact status line per `--interval`. Throttling is reported as
+/// the change since the previous line, which shows active throttling far
+/// better than the lifetime totals; memory adds its recent growth rate.
+fn run_watch(cli: &Cli) {
+    use std::io::Write;
+
+    let interval = std::time::Duration::from_secs_f64(cli.interval);
+    let mut previous_throttling: Option<CpuThrottleStats> = None;
+    let mut previous_usage: Option<(std::time::Instant, CgroupCpuUsage)> = None;
+    let mut memory_trend = memtrend::MemoryTrend::default();
+    loop {
+        let cgroup_path = target_cgroup_path(cli);
+        let available_cpus = num_cpus::get();
+        let quota = get_cgroup_cpu_quota_for_path(&cgroup_path);
+        let mut line = format!("{} cpu: {} CPUs", timestamp::now_iso8601(), available_cpus);
+        if let Some(quota) = quota {
+            line.push_str(&format!(" (quota {:.2})", quota));
+        }
+
+        // Utilization over the interval, relative to the quota (or visible CPUs)
+        let usage = get_cgroup_cpu_usage_for_path(&cgroup_path).map(|u| (std::time::Instant::now(), u));
+        if let (Some((then, prev)), Some((now, current))) = (previous_usage, usage) {
+            let wall_usec = now.duration_since(then).as_micros() as f64;
+            if wall_usec > 0.0 {
+                let used = current.usage_usec.saturating_sub(prev.usage_usec) as f64 / wall_usec;
+                let capacity = quota.unwrap_or(available_cpus as f64);
+                line.push_str(&format!(
+                    ", using {:.2} of {:.2} CPUs ({:.0}%)",
+                    used,
+                    capacity,
+                    used / capacity * 100.0
+                ));
+            }
+        }
+        previous_usage = usage;
+
+        let throttling = get_cgroup_cpu_throttling_for_path(&cgroup_path);
+        match (previous_throttling, throttling) {
+            (Some(prev), Some(now)) => line.push_str(&format!(
+                ", throttled: +{} periods ({}ms) this interval",
+                now.nr_throttled.saturating_sub(prev.nr_throttled),
+                now.throttled_usec.saturating_sub(prev.throttled_usec) / 1000
+            )),
+            (None, Some(now)) => line.push_str(&format!(
+                ", throttled: {} periods ({}ms) total",
+                now.nr_throttled,
+                now.throttled_usec / 1000
+            )),
+            _ => {}
+        }
+        previous_throttling = throttling;
+
+        let (system_total, system_available) = get_system_memory_from_proc();
+        let usage = get_cgroup_memory_usage_stable(&cgroup_path, cli.stable_reads);
+        let limit = memory_limit_for_cli(cli, &cgroup_path);
+        match (usage, limit) {
+            (Some(usage), Some(limit)) => line.push_str(&format!(
+                " | memory: {} of {} ({:.1}%)",
+                format_bytes(usage, cli.units),
+                format_bytes(limit, cli.units),
+                usage as f64 / limit as f64 * 100.0
+            )),
+            _ => line.push_str(&format!(
+                " | memory: {} of {} available",
+                format_bytes(system_available, cli.units),
+                format_bytes(system_total, cli.units)
+            )),
+        }
+        if let Some(usage) = usage {
+            memory_trend.push(std::time::Instant::now(), usage);
+            if let Some(rate) = memory_trend.bytes_per_sec() {
+                let verb = if rate < 0.0 { "shrinking" } else { "growing" };
+                // Without a limit the line shows system memory, so name what is changing
+                let subject = if limit.is_some() { "" } else { "cgroup usage " };
+                line.push_str(&format!(", {}{} {}/s", subject, verb, format_bytes(rate.abs() as u64, cli.units)));
+                if let Some(seconds) = limit.and_then(|limit| memtrend::seconds_to_limit(rate, usage, limit)) {
+                    line.push_str(&format!(", ~{} to limit", memtrend::format_eta(seconds)));
+                }
+            }
+        }
+
+        println!("{}", line);
+        let _ = std::io::stdout().flush();
+        std::thread::sleep(interval);
+    }
+}
+
+/// Fields that change on nearly every sample; `--diff-live` tracks them via
+/// the derived `memory.usage_band` instead of printing every fluctuation.
+const DIFF_LIVE_VOLATILE_PREFIXES: &[&str] = &[
+    "timestamp",
+    "cpu.steal_percent",
+    "cpu.per_cpu_busy",
+    "cpu.throttling",
+    "cpu.usage",
+    "cpu.pressure",
+    "memory.system_available_bytes",
+    "memory.system_used_bytes",
+    "memory.cgroup_memory_usage_bytes",
+    "memory.cgroup_swap_usage_bytes",
+    "memory.usage_breakdown",
+    "memory.effective_usage_bytes",
+    "memory.reclaimable_bytes",
+    "memory.unreclaimable_bytes",
+    "memory.usage_percent_of_limit",
+    "memory.reclaim_adjusted_percent_of_limit",
+    "memory.memory_headroom",
+    "memory.effective_available_bytes",
+    "memory.pressure",
+    "memory.anon_huge_pages_bytes",
+    "memory.kernel",
+    "memory.page_cache",
+    "memory.system_free_bytes",
+    "memory.overcommit.committed_as_bytes",
+    "memory.shm.used_bytes",
+    "memory.shm.free_bytes",
+    "memory.tmpfs_mounts",
+    "memory.ksm",
+    "cgroup.zswap.current_bytes",
+    "process.oom_score",
+    "kernel_tunables.process_map_count",
+    "disks",
+    "io_pressure",
+];
+
+/// Usage thresholds (percent) whose crossing `--diff-live` reports.
+const DIFF_LIVE_USAGE_BANDS: &[f64] = &[50.0, 75.0, 90.0, 95.0];
+
+/// Re-gathers the detailed report every `--interval` seconds and prints only
+/// the fields that changed since the previous sample.
+fn run_diff_live(cli: &Cli) {
+    use std::io::Write;
+
+    let interval = std::time::Duration::from_secs_f64(cli.interval);
+    let mut previous: Option<BTreeMap<String, String>> = None;
+    loop {
+        let report = gather_detailed_report(cli);
+        let current = flatten_report_for_diff(&report);
+        let now = timestamp::now_iso8601();
+        match &previous {
+            None => println!("{} watching {} fields every {}s", now, current.len(), cli.interval),
+            Some(prev) => {
+                for (key, value) in &current {
+                    match prev.get(key) {
+                        Some(old) if old == value => {}
+                        Some(old) => println!("{} {}: {} -> {}", now, key, old, value),
+                        None => println!("{} {}: (absent) -> {}", now, key, value),
+                    }
+                }
+                for key in prev.keys().filter(|key| !current.contains_key(*key)) {
+                    println!("{} {}: {} -> (absent)", now, key, prev[key]);
+                }
+            }
+        }
+        let _ = std::io::stdout().flush();
+        previous = Some(current);
+        std::thread::sleep(interval);
+    }
+}
+
+fn flatten_report_for_diff(report: &DetailedReport) -> BTreeMap<String, String> {
+    fn flatten(prefix: &str, value: &serde_json::Value, out: &mut BTreeMap<String, String>) {
+        if DIFF_LIVE_VOLATILE_PREFIXES.iter().any(|p| prefix.starts_with(p)) {
+            return;
+        }
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map {
+                    let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                    flatten(&path, child, out);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (idx, child) in items.iter().enumerate() {
+                    flatten(&format!("{}[{}]", prefix, idx), child, out);
+                }
+            }
+            other => {
+                out.insert(prefix.to_string(), other.to_string());
+            }
+        }
+    }
+
+    let mut flat = BTreeMap::new();
+    if let Ok(value) = serde_json::to_value(report) {
+        flatten("", &value, &mut flat);
+    }
+
+    let memory = &report.memory;
+    let usage_percent = match (memory.cgroup_memory_usage_bytes, memory.cgroup_memory_limit_bytes) {
+        (Some(usage), Some(limit)) if limit > 0 => usage as f64 / limit as f64 * 100.0,
+        _ if memory.system_total_bytes > 0 => {
+            memory.system_used_bytes as f64 / memory.system_total_bytes as f64 * 100.0
+        }
+        _ => 0.0,
+    };
+    let band = match DIFF_LIVE_USAGE_BANDS.iter().rev().find(|&&threshold| usage_percent >= threshold) {
+        Some(threshold) => format!(">={}%", threshold),
+        None => format!("<{}%", DIFF_LIVE_USAGE_BANDS[0]),
+    };
+    flat.insert("memory.usage_band".to_string(), band);
+
+    let constraints = report.constraints();
+    flat.insert("constraints.cpu".to_string(), constraints.cpu.to_string());
+    flat.insert("constraints.cpu_reason".to_string(), constraints.cpu_reason.unwrap_or("none").to_string());
+    flat.insert("constraints.memory".to_string(), constraints.memory.to_string());
+    flat
+}
+
+/// One line naming all three numbers that matter: the quota, the CPUs the
+/// process may be scheduled on, and the system total, e.g.
+/// "Constrained to ~1.5 CPUs (quota) on 2 assigned CPUs of 16 system CPUs".
+fn simple_cpu_line(
+    reason: Option<&str>,
+    cpu_quota: Option<f64>,
+    assigned_cpus: usize,
+    system_logical_cpus: usize,
+) -> Option<String> {
+    let placement = if assigned_cpus < system_logical_cpus {
+        format!("{} assigned CPUs of {} system CPUs", assigned_cpus, system_logical_cpus)
+    } else {
+        format!("all {} system CPUs", system_logical_cpus)
+    };
+    match (reason?, cpu_quota) {
+        ("cpu.max quota", Some(quota)) => {
+            Some(format!("Constrained to ~{} CPUs (quota) on {}", format_cpu_count(quota), placement))
+        }
+        (mechanism @ ("cpuset" | "affinity"), quota) => {
+            let mut line = format!("Constrained to {} ({})", placement, mechanism);
+            if let Some(quota) = quota {
+                line.push_str(&format!(", quota ~{} CPUs", format_cpu_count(quota)));
+            }
+            Some(line)
+        }
+        _ => None,
+    }
+}
+
+/// 1.5 -> "1.5", 2.0 -> "2", 0.333 -> "0.33".
+fn format_cpu_count(cpus: f64) -> String {
+    let text = format!("{:.2}", cpus);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Effective CPUs (the smallest of quota, cpuset and affinity) and memory
+/// ceiling (the smallest limit, including inherited ones) for the preflight checks.
+fn preflight_resources(
+    cgroup_path: &str,
+    system_logical_cpus: usize,
+    cpu_limits: [Option<f64>; 3],
+    memory_limits: [Option<u64>; 2],
+    system_total: u64,
+) -> (f64, u64) {
+    let effective_cpus = cpu_limits.into_iter().flatten().fold(system_logical_cpus as f64, f64::min);
+    let memory_ceiling = memory_limits
+        .into_iter()
+        .chain([get_effective_memory_ceiling_for_path(cgroup_path)])
+        .flatten()
+        .fold(system_total, u64::min);
+    (effective_cpus, memory_ceiling)
+}
+
+/// Requirements from `--min-cpus`/`--min-memory` that the effective
+/// (cgroup-aware) resources do not meet.
+fn preflight_failures(cli: &Cli, effective_cpus: f64, memory_ceiling: u64) -> Vec<String> {
+    let mut failures = Vec::new();
+    if let Some(min_cpus) = cli.min_cpus
+        && effective_cpus < min_cpus
+    {
+        failures.push(format!("{:.2} CPUs available, {} required", effective_cpus, min_cpus));
+    }
+    if let Some(min_memory) = cli.min_memory
+        && memory_ceiling < min_memory
+    {
+        failures.push(format!(
+            "{} memory available, {} required",
+            format_bytes(memory_ceiling, cli.units),
+            format_bytes(min_memory, cli.units)
+        ));
+    }
+    failures
+}
+
+/// Every way the environment differs from a `--check-config` manifest, as
+/// (key, expected, found). CPUs match within `cpu_tolerance`; memory is
+/// compared in whole pages, since the kernel rounds limits down to one.
+fn check_config_mismatches(
+    config: &CheckConfig,
+    cpu_quota: Option<f64>,
+    memory_limit: Option<u64>,
+    pids_limit: Option<u64>,
+    cpu_tolerance: f64,
+    units: Units,
+) -> Vec<(&'static str, String, String)> {
+    let page_size = get_page_size().unwrap_or(4096);
+    [
+        config_mismatch("cpus", config.cpus, cpu_quota, |expected, actual| (expected - actual).abs() <= cpu_tolerance, |cpus| format!("{:.2} CPUs", cpus)),
+        config_mismatch("memory", config.memory, memory_limit, |expected, actual| expected / page_size == actual / page_size, |bytes| format_bytes(bytes, units)),
+        config_mismatch("pids", config.pids, pids_limit, |expected, actual| expected == actual, |pids| pids.to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn config_mismatch<T: Copy>(
+    key: &'static str,
+    expected: Option<manifest::Expected<T>>,
+    actual: Option<T>,
+    matches: impl Fn(T, T) -> bool,
+    show: impl Fn(T) -> String,
+) -> Option<(&'static str, String, String)> {
+    let expected = expected?.limit();
+    let same = match (expected, actual) {
+        (Some(expected), Some(actual)) => matches(expected, actual),
+        (None, None) => true,
+        _ => false,
+    };
+    let show = |value: Option<T>| value.map_or_else(|| "unlimited".to_string(), &show);
+    (!same).then(|| (key, show(expected), show(actual)))
+}
+
+/// Compares the cgroup CPU quota (same math and root fallback as the rest of
+/// the report) against `--assert-cpus`.
+fn assert_cpus_failure(expected: f64, tolerance: f64, cgroup_cpu_quota: Option<f64>) -> Option<String> {
+    match cgroup_cpu_quota {
+        Some(actual) if (actual - expected).abs() <= tolerance => None,
+        Some(actual) => Some(format!("expected {:.2} CPUs (±{}), found a quota of {:.2} CPUs", expected, tolerance, actual)),
+        None => Some(format!("expected {:.2} CPUs (±{}), found no CPU quota", expected, tolerance)),
+    }
+}
+
+/// Maximum length of the `--summary` line, so it fits in chat/alert previews.
+const SUMMARY_MAX_CHARS: usize = 200;
+
+/// Builds the `--summary` line, with byte values in `units`.
+fn summary_line(
+    available_cpus: usize,
+    system_logical_cpus: usize,
+    cgroup_cpu_quota: Option<f64>,
+    constraints: &Constraints,
+    system_total: u64,
+    cgroup_path: &str,
+    units: Units,
+) -> String {
+    let cpus = match cgroup_cpu_quota {
+        Some(quota) if quota < available_cpus as f64 => format!("{:.2}", quota),
+        _ => available_cpus.to_string(),
+    };
+    let memory = constraints.memory_limit_bytes;
+    let constrained = constraints.any();
+
+    let mut line = format!(
+        "systemcheck: {} of {} CPUs, {} of {} memory, ",
+        cpus,
+        system_logical_cpus,
+        format_bytes(memory.unwrap_or(system_total), units),
+        format_bytes(system_total, units)
+    );
+    if !constrained {
+        line.push_str("unconstrained");
+        return line;
+    }
+
+    let verdict = if cgroup_path.is_empty() || cgroup_path == "/" {
+        "constrained".to_string()
+    } else {
+        format!("constrained by {}", cgroup_path)
+    };
+    if line.chars().count() + verdict.chars().count() <= SUMMARY_MAX_CHARS {
+        line.push_str(&verdict);
+    } else {
+        // Keep the tail of the path, which identifies the unit/job
+        let budget = SUMMARY_MAX_CHARS.saturating_sub(line.chars().count() + "constrained by …".chars().count());
+        let tail: String = cgroup_path.chars().rev().take(budget).collect::<Vec<_>>().into_iter().rev().collect();
+        line.push_str("constrained by …");
+        line.push_str(&tail);
+    }
+    line
+}
+
+/// Version of the `--bench-env` layout. Keys are only ever appended; bump
+/// this if one is renamed or its meaning changes.
+const BENCH_ENV_FORMAT: u32 = 1;
+
+/// Provenance block for benchmark results: one `key=value` per line, in a
+/// fixed order, with raw numbers and "none" for absent values.
+fn bench_env_block(
+    cgroup_path: &str,
+    available_cpus: usize,
+    system_logical_cpus: usize,
+    cgroup_cpu_quota: Option<f64>,
+    cgroup_memory_limit: Option<u64>,
+    system_total: u64,
+) -> String {
+    let none = || "none".to_string();
+    let frequency = cpufreq::read_cpu_frequency_info();
+    let (swap_total, _) = get_system_swap_from_proc();
+    let fields = [
+        ("format", BENCH_ENV_FORMAT.to_string()),
+        ("systemcheck_version", VERSION.to_string()),
+        ("kernel", read_trimmed("/proc/sys/kernel/osrelease").unwrap_or_else(none)),
+        ("system_logical_cpus", system_logical_cpus.to_string()),
+        ("available_cpus", available_cpus.to_string()),
+        ("cpu_quota", cgroup_cpu_quota.map_or_else(none, |quota| format!("{:.2}", quota))),
+        ("cpuset", get_cgroup_cpuset_for_path(cgroup_path).map_or_else(none, |cpus| cpulist::format_cpu_list(&cpus))),
+        ("cpus_allowed", read_cpus_allowed_list().unwrap_or_else(none)),
+        ("cpu_governor", frequency.governor.unwrap_or_else(none)),
+        ("cpu_turbo", frequency.turbo_enabled.map_or_else(none, |on| on.to_string())),
+        ("system_memory_bytes", system_total.to_string()),
+        ("memory_limit_bytes", cgroup_memory_limit.map_or_else(none, |limit| limit.to_string())),
+        ("thp_enabled", read_sysfs_choice(THP_ENABLED).unwrap_or_else(none)),
+        ("swap_total_bytes", swap_total.to_string()),
+        ("cgroup_swap_limit_bytes", get_cgroup_swap_limit_for_path(cgroup_path).map_or_else(none, |limit| limit.to_string())),
+    ];
+    fields.iter().map(|(key, value)| format!("{}={}\n", key, value)).collect()
+}
+
+const THP_ENABLED: &str = "/sys/kernel/mm/transparent_hugepage/enabled";
+const THP_DEFRAG: &str = "/sys/kernel/mm/transparent_hugepage/defrag";
+
+/// The selected entry of a sysfs choice file such as
+/// "always [madvise] never".
+fn read_sysfs_choice(path: &str) -> Option<String> {
+    parse_sysfs_choice(&read_trimmed(path)?).map(str::to_string)
+}
+
+fn parse_sysfs_choice(contents: &str) -> Option<&str> {
+    let start = contents.find('[')? + 1;
+    let end = contents[start..].find(']')? + start;
+    Some(&contents[start..end])
+}
+
+fn steal_sample_window(cli: &Cli) -> std::time::Duration {
+    std::time::Duration::from_millis(cli.steal_sample_ms)
+}
+
+/// Steal above this share of CPU time means noisy neighbours on the host.
+const STEAL_WARN_PERCENT: f64 = 5.0;
+
+fn print_cpu_info(cli: &Cli) {
+    println!("CPU Information:");
+    println!("----------------");
+
+    let cgroup_path = target_cgroup_path(cli);
+    let cpu_quota = cpu_quota_for_cli(cli, &cgroup_path);
+
+    // Get actual system CPUs (not limited by cgroups)
+    let system_logical_cpus = get_system_cpu_count();
+    let system_physical_cpus = get_system_physical_cpu_count();
+
+    // Get cgroup-limited CPUs
+    let available_cpus = num_cpus::get();
+
+    println!("  System Logical CPUs:     {} threads", system_logical_cpus);
+    let online_cpus = get_system_online_cpu_count();
+    if online_cpus != system_logical_cpus {
+        println!("  Online CPUs:             {} of {} present", online_cpus, system_logical_cpus);
+    }
+    println!("  System Physical CPUs:    {} cores", system_physical_cpus);
+    println!("  Available CPUs (cgroup): {}", available_cpus);
+
+    if available_cpus < system_logical_cpus {
+        println!("  ⚠️  CPU is constrained by cgroups to {} of {} system CPUs",
+                 available_cpus, system_logical_cpus);
+    }
+
+    print_cpu_views(&get_cpu_views(system_logical_cpus, cpu_quota, &cgroup_path));
+
+    if let Some(cpu_quota) = cpu_quota {
+        println!("  CGroup CPU Quota:        {:.2} CPUs", cpu_quota);
+        if is_sub_core_quota(Some(cpu_quota)) {
+            println!("  {}", sub_core_quota_warning(cpu_quota));
+        }
+    }
+    if let Some(warning) = quota_cpuset_mismatch_warning(
+        cpu_quota,
+        get_cgroup_cpuset_count_for_path(&cgroup_path),
+    ) {
+        println!("  ⚠️  {}", warning);
+    }
+
+    if get_cgroup_cpu_idle_for_path(&cgroup_path) == Some(true) {
+        println!("  {}", CPU_IDLE_WARNING);
+    }
+
+    if let Some(burst) = get_cgroup_cpu_burst_for_path(&cgroup_path) {
+        println!("  CGroup CPU Burst:        {} usec per period", burst);
+        println!("  Note: a burst allowance is configured; this cgroup can briefly run above its quota.");
+    }
+
+    if let Some(throttling) = get_cgroup_cpu_throttling_for_path(&cgroup_path)
+        && throttling.nr_periods > 0
+    {
+        println!("  CPU Throttling:          {} of {} periods throttled ({:.1}s total)",
+            throttling.nr_throttled,
+            throttling.nr_periods,
+            throttling.throttled_usec as f64 / 1_000_000.0);
+        if let (Some(nr_bursts), Some(burst_usec)) = (throttling.nr_bursts, throttling.burst_usec)
+            && nr_bursts > 0
+        {
+            println!("  CPU Burst Used:          {} bursts ({:.1}s above quota)", nr_bursts, burst_usec as f64 / 1_000_000.0);
+        }
+    }
+
+    if let Some(pressure) = psi::read_pressure("cpu", &cgroup_path) {
+        println!("  {:<25}{}", format!("CPU PSI ({}):", pressure.source), pressure.describe());
+    }
+
+    if let Some(usage) = get_cgroup_cpu_usage_for_path(&cgroup_path) {
+        match (usage.user_usec, usage.system_usec) {
+            (Some(user), Some(system)) => println!("  CGroup CPU Time:         {} usec (user {}, system {})",
+                usage.usage_usec, user, system),
+            _ => println!("  CGroup CPU Time:         {} usec", usage.usage_usec),
+        }
+    }
+
+    let sched = get_process_sched_info();
+    match sched.rt_priority {
+        Some(prio) => println!("  Scheduling Policy:       {} (rt priority {})", sched.policy, prio),
+        None => match sched.autogroup_nice {
+            // Autogroups share CPU between sessions first, so their nice can matter more
+            Some(group) => println!("  Scheduling Policy:       {} (nice {}, autogroup nice {})", sched.policy, sched.nice, group),
+            None => println!("  Scheduling Policy:       {} (nice {})", sched.policy, sched.nice),
+        },
+    }
+    if sched.policy == "SCHED_IDLE" {
+        println!("  ⚠️  Process runs under SCHED_IDLE and only gets CPU time nothing else wants");
+    } else if sched.nice >= 10 {
+        println!("  ⚠️  Process runs at nice {} and yields CPU to normal-priority work", sched.nice);
+    }
+
+    let freq = cpufreq::read_cpu_frequency_info();
+    if let Some(governor) = &freq.governor {
+        if freq.governors_differ {
+            println!("  CPU Frequency Governor:  {} (cpu0; other CPUs differ)", governor);
+        } else {
+            println!("  CPU Frequency Governor:  {}", governor);
+        }
+    }
+    if let Some(turbo) = freq.turbo_enabled {
+        println!("  Turbo/Boost:             {}", if turbo { "enabled" } else { "disabled" });
+    }
+    let is_x86 = cfg!(any(target_arch = "x86", target_arch = "x86_64"));
+    if is_x86 && freq.is_powersave_throttled() {
+        println!("  ⚠️  CPU governor is 'powersave'; cores may run well below their rated speed");
+    }
+
+    let cache_info = cpucache::read_cpu_caches();
+    if !cache_info.caches.is_empty() {
+        println!("  CPU Caches:");
+        for cache in &cache_info.caches {
+            let name = match (cache.level, cache.cache_type.as_str()) {
+                (Some(level), "Data") => format!("L{}d", level),
+                (Some(level), "Instruction") => format!("L{}i", level),
+                (Some(level), _) => format!("L{}", level),
+                (None, _) => "Cache".to_string(),
+            };
+            match cache.shared_by_cpus {
+                Some(shared) => println!("    {:<4} {} x{} (shared by {} CPUs)",
+                    name, format_bytes(cache.size_bytes, cli.units), cache.instances, shared),
+                None => println!("    {:<4} {}", name, format_bytes(cache.size_bytes, cli.units)),
+            }
+        }
+        if let Some(l3_total) = cache_info.l3_total_bytes {
+            println!("    Total L3: {}", format_bytes(l3_total, cli.units));
+        }
+    }
+
+    let core_groups = coretypes::read_core_groups();
+    if core_groups.len() > 1 {
+        let groups: Vec<String> = core_groups
+            .iter()
+            .map(|group| match group.max_khz {
+                Some(khz) => format!("{} {} (max {:.1} GHz)", group.count, group.kind, khz as f64 / 1_000_000.0),
+                None => format!("{} {}", group.count, group.kind),
+            })
+            .collect();
+        println!("  Core Types:              {}", groups.join(", "));
+    }
+    let workers = recommended_worker_count(available_cpus, cpu_quota, &core_groups, cli.perf_cores_only);
+    println!("  Recommended Workers:     {}", workers);
+
+    let env_threads = read_thread_env_vars();
+    for (name, value) in &env_threads {
+        println!("  {:<24} {}", format!("{}:", name), value);
+    }
+    for warning in thread_env_warnings(&env_threads, workers, cpu_quota, available_cpus) {
+        println!("  ⚠️  {}", warning);
+    }
+
+    let cpu_sample = procstat::sample_proc_stat(steal_sample_window(cli));
+
+    // Bare metal reports ~0 steal; stay silent unless it is measurable
+    if let Some(steal) = cpu_sample.as_ref().and_then(|(before, after)| procstat::aggregate_steal_percent(before, after))
+        && steal >= 0.1
+    {
+        println!("  CPU Steal Time:          {:.1}%", steal);
+        if steal > STEAL_WARN_PERCENT {
+            println!("  ⚠️  The hypervisor is taking {:.1}% of CPU time for other guests (noisy neighbours)", steal);
+        }
+    }
+
+    if let Some((before, after)) = &cpu_sample {
+        let cpuset = get_cgroup_cpuset_for_path(&cgroup_path);
+        let per_cpu = procstat::per_cpu_busy(before, after, cpuset.as_deref());
+        if !per_cpu.is_empty() {
+            let scope = if cpuset.is_some() { "cpuset CPUs" } else { "all CPUs" };
+            println!("  Per-CPU Busy ({}, over {} ms):", scope, cli.steal_sample_ms);
+            for row in per_cpu.chunks(8) {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cpu| format!("{:>6} {:>5.1}%", format!("cpu{}", cpu.cpu), cpu.busy_percent))
+                    .collect();
+                println!("   {}", cells.join(""));
+            }
+        }
+    }
+
+    let isa_info = isa::read_isa_info();
+    if !isa_info.features.is_empty() {
+        let features: Vec<String> = isa_info.features.iter().map(|f| f.to_uppercase()).collect();
+        match isa_info.x86_64_level {
+            Some(level) => println!("  ISA: {} (x86-64-v{})", features.join(", "), level),
+            None => println!("  ISA: {}", features.join(", ")),
+        }
+    }
+
+    let cpu_mitigations = mitigations::read_capacity_mitigations();
+    if cli.verbose >= 2 {
+        if !cpu_mitigations.is_empty() {
+            println!("  SMT-related Mitigations:");
+            for mitigation in &cpu_mitigations {
+                println!("    {}: {}", mitigation.name, mitigation.status);
+            }
+        }
+    } else if mitigations::explains_smt_off(&cpu_mitigations) {
+        println!("  Note: SMT is disabled by a CPU vulnerability mitigation, so only one thread per core is online (see -vv)");
+    }
+}
+
+struct SchedInfo {
+    policy: String,
+    nice: i32,
+    autogroup_nice: Option<i32>,
+    rt_priority: Option<i32>,
+}
+
+/// Scheduling policy, nice value, and (for realtime policies) RT priority of
+/// the current process.
+fn get_process_sched_info() -> SchedInfo {
+    let raw_policy = unsafe { libc::sched_getscheduler(0) };
+    let policy = match raw_policy & !libc::SCHED_RESET_ON_FORK {
+        libc::SCHED_OTHER => "SCHED_OTHER".to_string(),
+        libc::SCHED_FIFO => "SCHED_FIFO".to_string(),
+        libc::SCHED_RR => "SCHED_RR".to_string(),
+        libc::SCHED_BATCH => "SCHED_BATCH".to_string(),
+        libc::SCHED_IDLE => "SCHED_IDLE".to_string(),
+        libc::SCHED_DEADLINE => "SCHED_DEADLINE".to_string(),
+        _ if raw_policy < 0 => "unknown".to_string(),
+        other => format!("unknown ({})", other),
+    };
+
+    let rt_priority = if policy == "SCHED_FIFO" || policy == "SCHED_RR" {
+        let mut param = libc::sched_param { sched_priority: 0 };
+        if unsafe { libc::sched_getparam(0, &mut param) } == 0 {
+            Some(param.sched_priority)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    // getpriority can legitimately return -1, so a failure just reads as nice -1
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+
+    let autogroup_nice = (read_trimmed("/proc/sys/kernel/sched_autogroup_enabled").as_deref() == Some("1"))
+        .then(|| read_trimmed("/proc/self/autogroup").as_deref().and_then(parse_autogroup_nice))
+        .flatten();
+
+    SchedInfo { policy, nice, autogroup_nice, rt_priority }
+}
+
+/// `/proc/self/autogroup` reads "/autogroup-327 nice 0".
+fn parse_autogroup_nice(contents: &str) -> Option<i32> {
+    let (_, nice) = contents.split_once(" nice ")?;
+    nice.trim().parse().ok()
+}
+
+/// Number of parallel workers that can run without being throttled: the
+/// available CPUs, capped by the whole CPUs in the quota (at least 1), and
+/// optionally by the number of performance cores on hybrid machines.
+fn recommended_worker_count(
+    available_cpus: usize,
+    cgroup_cpu_quota: Option<f64>,
+    core_groups: &[coretypes::CoreGroup],
+    perf_cores_only: bool,
+) -> usize {
+    let mut workers = available_cpus.max(1);
+    if let Some(quota) = cgroup_cpu_quota {
+        workers = workers.min((quota.floor() as usize).max(1));
+    }
+    if perf_cores_only
+        && let Some(perf) = core_groups.iter().find(|group| group.kind == "performance")
+    {
+        workers = workers.min(perf.count.max(1));
+    }
+    workers
+}
+
+/// Environment variables that size thread pools in common runtimes.
+const THREAD_ENV_VARS: &[&str] = &[
+    "OMP_NUM_THREADS",
+    "MKL_NUM_THREADS",
+    "OPENBLAS_NUM_THREADS",
+    "GOMAXPROCS",
+    "RAYON_NUM_THREADS",
+    "JULIA_NUM_THREADS",
+];
+
+fn read_thread_env_vars() -> BTreeMap<String, String> {
+    THREAD_ENV_VARS
+        .iter()
+        .filter_map(|name| std::env::var(name).ok().map(|value| (name.to_string(), value)))
+        .collect()
+}
+
+/// Flags thread-count variables that oversubscribe the recommended worker
+/// count, and the unset case where OpenMP would spawn one thread per visible
+/// CPU despite a smaller quota.
+fn thread_env_warnings(
+    env_threads: &BTreeMap<String, String>,
+    recommended_workers: usize,
+    cgroup_cpu_quota: Option<f64>,
+    available_cpus: usize,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (name, value) in env_threads {
+        // OMP_NUM_THREADS may be a nested list ("8,2"); the first level is the one that matters here
+        let threads = value.split(',').next().and_then(|v| v.trim().parse::<usize>().ok());
+        if let Some(threads) = threads
+            && threads > recommended_workers
+        {
+            warnings.push(format!(
+                "{}={} exceeds the recommended {} worker(s); threads will contend for CPU",
+                name, threads, recommended_workers
+            ));
+        }
+    }
+    if env_threads.is_empty()
+        && let Some(quota) = cgroup_cpu_quota
+        && quota < available_cpus as f64
+    {
+        warnings.push(format!(
+            "no thread-count variables are set and the CPU quota is {:.2}; OpenMP/BLAS will default to all {} visible CPUs (set OMP_NUM_THREADS={})",
+            quota, available_cpus, recommended_workers
+        ));
+    }
+    warnings
+}
+
+/// Flags a CPU quota and cpuset that disagree by more than one CPU, e.g. a
+/// `cpu.max` of 8 CPUs over a 2-CPU cpuset (the extra quota can never be used)
+/// or a 2-CPU quota over 8 cpuset CPUs (threads sized to the cpuset throttle).
+fn quota_cpuset_mismatch_warning(cgroup_cpu_quota: Option<f64>, cpuset_cpus: Option<usize>) -> Option<String> {
+    let quota = cgroup_cpu_quota?;
+    let cpuset = cpuset_cpus?;
+    let rounded = quota.round() as usize;
+    if rounded.abs_diff(cpuset) <= 1 {
+        return None;
+    }
+    Some(format!(
+        "CPU quota ({:.2} CPUs) and cpuset ({} CPUs) disagree; only {} CPUs' worth of work can run",
+        quota,
+        cpuset,
+        quota.min(cpuset as f64)
+    ))
+}
+
+/// Names the mechanism limiting CPUs below the logical count. When several
+/// apply, the tightest one wins (ties go to quota, then cpuset, then
+/// affinity); idle scheduling is reported only when nothing else limits.
+fn cpu_constraint_reason(
+    system_logical_cpus: usize,
+    cpu_quota: Option<f64>,
+    cpuset_cpus: Option<usize>,
+    affinity_cpus: Option<usize>,
+    cpu_idle: bool,
+) -> Option<&'static str> {
+    let quota = cpu_quota.filter(|quota| (quota.floor() as usize) < system_logical_cpus);
+    let cpuset = cpuset_cpus.filter(|&count| count < system_logical_cpus).map(|count| count as f64);
+    let affinity = affinity_cpus.filter(|&count| count < system_logical_cpus).map(|count| count as f64);
+
+    let mut tightest: Option<(&'static str, f64)> = None;
+    for (reason, limit) in [("cpu.max quota", quota), ("cpuset", cpuset), ("affinity", affinity)] {
+        if let Some(limit) = limit
+            && tightest.is_none_or(|(_, best)| limit < best)
+        {
+            tightest = Some((reason, limit));
+        }
+    }
+    match tightest {
+        Some((reason, _)) => Some(reason),
+        None if cpu_idle => Some("cpu.idle"),
+        None => None,
+    }
+}
+
+fn get_cpu_views(system_logical_cpus: usize, cpu_quota: Option<f64>, cgroup_path: &str) -> CpuViews {
+    let affinity_cpus = read_cpus_allowed_list()
+        .map(|list| cpulist::parse_cpu_list(&list).len())
+        .filter(|&count| count > 0)
+        .or_else(get_process_affinity_count);
+    let cpuset_cpus = get_cgroup_cpuset_count_for_path(cgroup_path);
+    let quota_cpus = cpu_quota.map(|quota| quota.ceil() as usize);
+
+    // "logical" unless something is below it; on ties prefer the most specific mechanism
+    let mut binding = ("logical", system_logical_cpus);
+    for (name, count) in [("affinity", affinity_cpus), ("cpuset", cpuset_cpus), ("quota", quota_cpus)] {
+        if let Some(count) = count
+            && count < system_logical_cpus
+            && count <= binding.1
+        {
+            binding = (name, count);
+        }
+    }
+
+    CpuViews {
+        logical_cpus: system_logical_cpus,
+        affinity_cpus,
+        cpuset_cpus,
+        quota_cpus,
+        binding: binding.0.to_string(),
+    }
+}
+
+fn print_cpu_views(views: &CpuViews) {
+    let cell = |name: &str, value: Option<usize>| {
+        let value = value.map_or("-".to_string(), |count| count.to_string());
+        if views.binding == name { format!("{}*", value) } else { value }
+    };
+    println!("  CPU Views:               {:>8} {:>9} {:>7} {:>6}", "logical", "affinity", "cpuset", "quota");
+    println!(
+        "                           {:>8} {:>9} {:>7} {:>6}   (* binding)",
+        cell("logical", Some(views.logical_cpus)),
+        cell("affinity", views.affinity_cpus),
+        cell("cpuset", views.cpuset_cpus),
+        cell("quota", views.quota_cpus)
+    );
+    if let (Some(affinity), Some(cpuset)) = (views.affinity_cpus, views.cpuset_cpus)
+        && affinity != cpuset
+    {
+        println!(
+            "  Note: affinity allows {} CPUs but the cgroup cpuset has {}; the process mask was narrowed (e.g. by taskset)",
+            affinity, cpuset
+        );
+    }
+}
+
+/// `Cpus_allowed_list` from /proc/self/status, e.g. "0-3,8-11".
+fn read_cpus_allowed_list() -> Option<String> {
+    let status = probelog::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+        .map(|value| value.trim().to_string())
+}
+
+/// Number of CPUs in the process's scheduler affinity mask.
+fn get_process_affinity_count() -> Option<usize> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return None;
+        }
+        Some(libc::CPU_COUNT(&set) as usize)
+    }
+}
+
+/// A quota below one full CPU (e.g. Kubernetes `cpu: 500m`) throttles every
+/// extra thread, even though all cpuset CPUs remain visible.
+fn is_sub_core_quota(cpu_quota: Option<f64>) -> bool {
+    cpu_quota.map(|quota| quota < 1.0).unwrap_or(false)
+}
+
+fn sub_core_quota_warning(cpu_quota: f64) -> String {
+    format!(
+        "⚠️  CPU quota is {:.2} — multi-threaded code will be heavily throttled; prefer 1 worker",
+        cpu_quota
+    )
+}
+
+const DOMAIN_INVALID_WARNING: &str =
+    "cgroup.type is \"domain invalid\": the hierarchy is misconfigured and controllers (cpu, memory limits) will not apply here";
+
+const CPU_IDLE_WARNING: &str =
+    "⚠️  cgroup is idle-scheduled (cpu.idle=1): it only runs when the system is otherwise idle, so throughput and latency are not guaranteed";
+
+fn print_memory_info(cli: &Cli) {
+    println!("Memory Information:");
+    println!("-------------------");
+
+    // Get real system memory from /proc/meminfo
+    let (system_total, system_available) = get_system_memory_from_proc();
+
+    println!("  System Total Memory:     {}", format_bytes(system_total, cli.units));
+    if mem_available_estimated() {
+        println!("  System Available Memory: {} (estimated: this kernel has no MemAvailable)", format_bytes(system_available, cli.units));
+    } else {
+        println!("  System Available Memory: {} (reclaimable included)", format_bytes(system_available, cli.units));
+    }
+    if let Some(free) = read_meminfo_value("MemFree") {
+        println!("  System Free Memory:      {} (unused)", format_bytes(free, cli.units));
+    }
+
+    let system_used = system_total.saturating_sub(system_available);
+    println!("  System Used Memory:      {}", format_bytes(system_used, cli.units));
+    let kernel = read_kernel_memory();
+    if let Some(unreclaimable) = kernel.slab_unreclaimable_bytes
+        && system_total > 0
+        && unreclaimable as f64 > system_total as f64 * SLAB_NOTE_FRACTION
+    {
+        println!("    of which {} is unreclaimable kernel slab, not held by any process",
+            format_bytes(unreclaimable, cli.units));
+    }
+    let page_cache = read_page_cache();
+    if let (Some(buffers), Some(cached)) = (page_cache.buffers_bytes, page_cache.cached_bytes) {
+        println!("  Page Cache:              {} (dirty {})",
+            format_bytes(buffers + cached, cli.units),
+            format_bytes(page_cache.dirty_bytes.unwrap_or(0), cli.units));
+    }
+    if let Some(warning) = dirty_pages_warning(&page_cache, system_total, cli.units) {
+        println!("  ⚠️  {}", warning);
+    }
+    if cli.verbose >= 2 {
+        let show = |bytes: Option<u64>| bytes.map_or("unknown".to_string(), |bytes| format_bytes(bytes, cli.units));
+        println!("  Kernel Memory:           slab {} ({} reclaimable, {} unreclaimable), stacks {}, page tables {}",
+            show(kernel.slab_bytes),
+            show(kernel.slab_reclaimable_bytes),
+            show(kernel.slab_unreclaimable_bytes),
+            show(kernel.kernel_stack_bytes),
+            show(kernel.page_tables_bytes));
+    }
+    println!("  {}", swap_line(cli.units));
+    if let Some(page_size) = get_page_size() {
+        let huge = read_meminfo_value("Hugepagesize")
+            .map_or("unknown".to_string(), |bytes| format_bytes(bytes, cli.units));
+        println!("  Page Size:               {} (default huge page {})", format_bytes(page_size, cli.units), huge);
+        if cli.verbose >= 2
+            && let Some(ksm) = ksm::read_ksm(page_size)
+        {
+            println!("  KSM:                     {}, {} saved ({} mappings of {} shared pages)",
+                if ksm.enabled { "enabled" } else { "disabled" },
+                format_bytes(ksm.saved_bytes, cli.units),
+                ksm.pages_sharing,
+                ksm.pages_shared);
+            if ksm.enabled && ksm.saved_bytes > 0 {
+                println!("  Note: KSM shares identical pages, so per-process and cgroup usage can add up to more than the RAM in use.");
+            }
+        }
+    }
+
+    let pools: Vec<hugepages::HugePagePool> =
+        hugepages::read_hugepage_pools().into_iter().filter(|pool| pool.total > 0).collect();
+    if !pools.is_empty() {
+        println!("  Huge Pages:");
+        for pool in &pools {
+            println!("    {:>9} pages: {} total, {} free, {} reserved ({})",
+                format_bytes(pool.size_bytes, cli.units),
+                pool.total,
+                pool.free,
+                pool.reserved,
+                format_bytes(pool.pool_bytes(), cli.units));
+        }
+        let pooled: u64 = pools.iter().map(|pool| pool.pool_bytes()).sum();
+        if system_total > 0 && pooled as f64 > system_total as f64 * HUGEPAGE_NOTE_FRACTION {
+            println!("  Note: {} is set aside as huge pages; it is not in MemAvailable and normal allocations cannot use it",
+                format_bytes(pooled, cli.units));
+        }
+    }
+
+    if let Some(overcommit) = overcommit::read_overcommit() {
+        let mut line = format!("  Overcommit Policy:       {}", overcommit.policy);
+        if let (Some(committed), Some(limit)) = (overcommit.committed_as_bytes, overcommit.commit_limit_bytes) {
+            line.push_str(&format!(" (committed {} of {} commit limit)",
+                format_bytes(committed, cli.units), format_bytes(limit, cli.units)));
+        }
+        println!("{}", line);
+        if let Some(warning) = commit_limit_warning(&overcommit) {
+            println!("  ⚠️  {}", warning);
+        }
+    }
+
+    if let Some(enabled) = read_sysfs_choice(THP_ENABLED) {
+        let defrag = read_sysfs_choice(THP_DEFRAG).unwrap_or_else(|| "unknown".to_string());
+        let in_use = read_meminfo_value("AnonHugePages").unwrap_or(0);
+        println!("  Transparent Huge Pages:  {} (defrag: {}), {} in use",
+            enabled, defrag, format_bytes(in_use, cli.units));
+    }
+
+    // Get the current cgroup path and check its memory limit
+    let cgroup_path = target_cgroup_path(cli);
+
+    match shm::read_shm(&cli.shm_path) {
+        Some(shm_info) => {
+            println!("  Shared Memory ({}): {} total, {} used, {} free",
+                shm_info.path,
+                format_bytes(shm_info.total_bytes, cli.units),
+                format_bytes(shm_info.used_bytes, cli.units),
+                format_bytes(shm_info.free_bytes, cli.units));
+            let memory_limit = memory_limit_for_cli(cli, &cgroup_path).filter(|&limit| limit < system_total);
+            for warning in shm::shm_warnings(&shm_info, num_cpus::get(), memory_limit) {
+                println!("  ⚠️  {}", warning);
+            }
+        }
+        None => println!("  Shared Memory ({}): not present", cli.shm_path),
+    }
+
+    let tmpfs_mounts = tmpfs::read_tmpfs_mounts();
+    for mount in &tmpfs_mounts {
+        // Every tmpfs at -vv; at -v only the one holding the temp directory
+        if mount.is_temp_dir || cli.verbose >= 2 {
+            println!("  {:<25}{} total, {} used{}",
+                format!("tmpfs ({}):", mount.mount_point),
+                format_bytes(mount.total_bytes, cli.units),
+                format_bytes(mount.used_bytes, cli.units),
+                if mount.is_temp_dir { " (temp directory)" } else { "" });
+        }
+    }
+    if let Some(warning) = tmpfs::tmpfs_warning(&tmpfs_mounts, memory_limit_for_cli(cli, &cgroup_path)) {
+        println!("  ⚠️  {}", warning);
+    }
+
+    let memory_constraints = Constraints::new(
+        None,
+        memory_limit_for_cli(cli, &cgroup_path),
+        read_cgroup_v2_bytes(&cgroup_path, "memory.high"),
+        system_total,
+    );
+    if let Some(enforcement) = memory_constraints.describe_memory_enforcement(cli.units) {
+        println!("  Limit Enforcement:       {}", enforcement);
+    }
+
+    if let Some(cgroup_limit) = memory_limit_for_cli(cli, &cgroup_path) {
+        println!("  CGroup Memory Limit:     {}", format_bytes(cgroup_limit, cli.units));
+
+        if cgroup_limit < system_total {
+            println!("  ⚠️  Memory is constrained by cgroups!");
+
+            if let Some(current_usage) = get_cgroup_memory_usage_stable(&cgroup_path, cli.stable_reads) {
+                let breakdown = get_cgroup_memory_breakdown_for_path(&cgroup_path);
+                let parts: Vec<String> = breakdown
+                    .iter()
+                    .flat_map(|b| [(b.anon, "anon"), (b.file, "file cache"), (b.kernel, "kernel")])
+                    .filter_map(|(bytes, label)| Some(format!("{} {}", format_bytes(bytes?, cli.units), label)))
+                    .collect();
+                if parts.is_empty() {
+                    println!("  CGroup Memory Usage:     {}", format_bytes(current_usage, cli.units));
+                } else {
+                    println!("  CGroup Memory Usage:     {} ({})",
+                        format_bytes(current_usage, cli.units), parts.join(" + "));
+                }
+
+                // Page cache and reclaimable slab go before the OOM killer fires, so judge pressure without them
+                let effective = pressure_memory_usage(Some(current_usage), breakdown.as_ref()).unwrap_or(current_usage);
+                let naive_percent = (current_usage as f64 / cgroup_limit as f64) * 100.0;
+                let usage_percent = (effective as f64 / cgroup_limit as f64) * 100.0;
+                println!("  Effective Usage:         {} ({:.1}% of limit, excluding reclaimable memory)",
+                    format_bytes(effective, cli.units), usage_percent);
+                if let Some(reclaimable) = breakdown.as_ref().and_then(MemoryUsageBreakdown::reclaimable) {
+                    println!("  Reclaimable:             {} (file cache + reclaimable slab)", format_bytes(reclaimable, cli.units));
+                }
+                if naive_percent - usage_percent >= RECLAIM_NOTE_POINTS {
+                    println!("  Note: usage is {:.1}% of the limit counting page cache, but only {:.1}% cannot be reclaimed; the kernel drops cache before it OOM-kills, so the lower figure is the real pressure",
+                        naive_percent, usage_percent);
+                }
+                if let Some((headroom, percent)) =
+                    memory_headroom(Some(cgroup_limit), Some(current_usage), system_total, system_available)
+                {
+                    println!("  Memory Headroom:         {} ({:.1}% of limit)", format_bytes(headroom, cli.units), percent);
+                }
+                if cli.mem_warn_threshold.exceeded(effective, cgroup_limit) {
+                    println!("  {}", emphasize(&format!(
+                        "⚠️  Non-reclaimable memory is at {:.1}% of the limit; the OOM killer is close", usage_percent)));
+                }
+            }
+        }
+    }
+
+    if let Some(nodes) = numa::read_node_restriction(&cgroup_path)
+        && nodes.allowed_total_bytes < system_total
+    {
+        println!("  NUMA Memory Nodes:       {} ({} of {})",
+            nodes.allowed_nodes, format_bytes(nodes.allowed_total_bytes, cli.units), format_bytes(system_total, cli.units));
+        println!("  ⚠️  Memory is constrained by cpuset.mems: only these nodes' memory is usable");
+    }
+    if let Some(mems) = numa::read_cpuset_mems(&cgroup_path) {
+        println!("  CPUSet Memory Nodes:     {}", cpulist::format_cpu_list(&mems));
+        if let Some(cpus) = get_cgroup_cpuset_for_path(&cgroup_path)
+            && let Some(warning) = numa::binding_mismatch_warning(&numa::nodes_of_cpus(&cpus), &mems)
+        {
+            println!("  ⚠️  {}", warning);
+        }
+    }
+
+    for (label, file) in [("High", "memory.high"), ("Low", "memory.low"), ("Min", "memory.min")] {
+        if let Some(bytes) = read_cgroup_v2_bytes(&cgroup_path, file)
+            && bytes > 0
+        {
+            println!("  {:<25}{}", format!("CGroup Memory {}:", label), format_bytes(bytes, cli.units));
+        }
+    }
+
+    match get_cgroup_swap_limit_for_path(&cgroup_path) {
+        Some(0) => println!("  CGroup Swap Limit:       {} (swap disabled for this cgroup)", format_bytes(0, cli.units)),
+        Some(limit) => println!("  CGroup Swap Limit:       {}", format_bytes(limit, cli.units)),
+        None => {}
+    }
+    if let Some(usage) = get_cgroup_swap_usage_for_path(&cgroup_path)
+        && usage > 0
+    {
+        println!("  CGroup Swap Usage:       {}", format_bytes(usage, cli.units));
+    }
+
+    if let Some(swappiness) = read_trimmed("/proc/sys/vm/swappiness") {
+        match get_cgroup_swappiness_for_path(&cgroup_path) {
+            Some(cgroup) => println!("  Swappiness:              {} (system), {} (cgroup)", swappiness, cgroup),
+            None if cgroupfs::mounts().hierarchy("memory") == Some("v2") => {
+                println!("  Swappiness:              {} (system; cgroup v2 has no per-cgroup swappiness)", swappiness)
+            }
+            None => println!("  Swappiness:              {} (system)", swappiness),
+        }
+    }
+
+    if let Some((ceiling, limiting_path)) = effective_memory_limit_for_path(&cgroup_path) {
+        println!("  Effective RAM Ceiling:   {} (lowest limit in cgroup hierarchy, set on {})",
+            format_bytes(ceiling, cli.units), limiting_path);
+    }
+    if let Some(ceiling) = get_effective_memory_swap_ceiling_for_path(&cgroup_path) {
+        println!("  Effective RAM+Swap Ceiling: {}", format_bytes(ceiling, cli.units));
+    }
+
+    if let Some(pressure) = psi::read_pressure("memory", &cgroup_path) {
+        println!("  {:<25}{}", format!("Memory PSI ({}):", pressure.source), pressure.describe());
+        if let Some(full) = pressure.full_avg10
+            && full > MEMORY_PSI_FULL_WARN
+        {
+            println!("  ⚠️  Tasks were fully stalled on memory reclaim {:.2}% of the last 10s; expect slowness before any OOM", full);
+        }
+    }
+
+    let oom_group = get_cgroup_oom_group_for_path(&cgroup_path) == Some(true);
+    let mut oom_kills = 0;
+    if let Some(events) = get_cgroup_memory_events_for_path(&cgroup_path, "memory.events") {
+        let counts: Vec<String> = [("low", events.low), ("high", events.high), ("max", events.max), ("oom", events.oom)]
+            .iter()
+            .filter_map(|(name, count)| Some(format!("{} {}", name, (*count)?)))
+            .collect();
+        if !counts.is_empty() {
+            println!("  CGroup Memory Events:    {}", counts.join(", "));
+        }
+        if let Some(kills) = events.oom_kill
+            && kills > 0
+        {
+            let noun = if kills == 1 { "kill has" } else { "kills have" };
+            println!("  {}", emphasize(&format!("⚠️  {} OOM {} occurred in this cgroup", kills, noun)));
+            oom_kills = kills;
+        }
+    }
+    if oom_group && oom_kills > 0 {
+        println!("  Note: memory.oom.group=1, so each kill took every process in the cgroup; the process that overshot may not be the one you noticed dying");
+    } else if oom_group {
+        println!("  Note: memory.oom.group=1; an OOM in this cgroup kills every process in it");
+    }
+
+    if let Some(stat) = get_cgroup_memory_breakdown_for_path(&cgroup_path) {
+        println!("  CGroup Memory Breakdown:");
+        let rows = [
+            ("Anonymous (not reclaimable)", stat.anon),
+            ("File cache (reclaimable)", stat.file),
+            ("Kernel (total)", stat.kernel),
+            ("Kernel stack", stat.kernel_stack),
+            ("Slab", stat.slab),
+            ("Socket buffers", stat.sock),
+            ("Shared memory (shmem)", stat.shmem),
+            ("Swap", stat.swap),
+        ];
+        for (label, value) in rows {
+            if let Some(bytes) = value {
+                println!("    {:<28} {}", format!("{}:", label), format_bytes(bytes, cli.units));
+            }
+        }
+    }
+}
+
+fn print_cgroup_info(cli: &Cli) {
+    println!("CGroup Information:");
+    println!("-------------------");
+
+    let version = detect_cgroup_version();
+    if version.as_deref() == Some("v2") {
+        println!("  CGroup Version: v2 (unified hierarchy)");
+    } else if version.as_deref() == Some("v1") {
+        println!("  CGroup Version: v1");
+    } else if version.as_deref() == Some("hybrid") {
+        println!("  CGroup Version: hybrid (v1 controllers alongside a cgroup2 mount)");
+        let versions = cgroupfs::mounts().controller_versions();
+        for hierarchy in ["v1", "v2"] {
+            let names: Vec<&str> = versions.iter().filter(|(_, v)| v.as_str() == hierarchy).map(|(name, _)| name.as_str()).collect();
+            println!("    {} controllers: {}", hierarchy, if names.is_empty() { "none".to_string() } else { names.join(", ") });
+        }
+    } else if !is_cgroupfs_mounted() {
+        println!("  CGroup Version: unavailable (not mounted)");
+        println!("  ⚠️  {}", CGROUP_UNMOUNTED_WARNING);
+    } else {
+        println!("  CGroup Version: Not detected");
+    }
+
+    let paths_by_controller = read_controller_paths();
+    if !paths_by_controller.is_empty() {
+        println!("  Current Process CGroups:");
+        for (controller, path) in &paths_by_controller {
+            println!("    {}: {}", controller, path);
+        }
+    }
+
+    // Show resource constraints for the current (or --cgroup-path) cgroup
+    let cgroup_path = target_cgroup_path(cli);
+    if let Some(path) = &cli.cgroup_path {
+        println!("  Inspecting CGroup: {} (--cgroup-path)", path);
+    } else {
+        match cgroup_path_resolution(cli).as_str() {
+            "direct" => {}
+            "unresolved" => println!("  Inspecting CGroup: {} (not found in the cgroup mounts)", cgroup_path),
+            strategy => println!("  Inspecting CGroup: {} (resolved via {})", cgroup_path, strategy),
+        }
+    }
+    if let Some(cgroup_type) = read_cgroup_v2_file(&cgroup_path, "cgroup.type") {
+        println!("  CGroup Type: {}", cgroup_type);
+        if cgroup_type == "domain invalid" {
+            println!("  ⚠️  {}", DOMAIN_INVALID_WARNING);
+        } else if cgroup_type == "threaded" {
+            println!("  Note: threaded cgroup; only threaded controllers (cpu, cpuset, pids) act here, memory limits come from the \"domain threaded\" root");
+        }
+    }
+    if !cgroup_path.is_empty() && cgroup_path != "/" {
+        println!("\n  Resource Constraints for Current CGroup:");
+
+        // CPU constraints
+        if let Some(cpu_quota) = get_cgroup_cpu_quota_for_path(&cgroup_path) {
+            println!("    CPU Quota: {:.2} CPUs", cpu_quota);
+        }
+
+        // Memory constraints
+        if let Some(mem_limit) = get_cgroup_memory_limit_for_path(&cgroup_path) {
+            println!("    Memory Limit: {}", format_bytes(mem_limit, cli.units));
+        }
+
+        for hugetlb in hugepages::read_hugetlb_limits(&cgroup_path) {
+            let limit = hugetlb.limit_bytes.map_or("unlimited".to_string(), |bytes| format_bytes(bytes, cli.units));
+            println!(
+                "    HugeTLB {} Limit: {} ({} in use)",
+                hugetlb.page_size,
+                limit,
+                format_bytes(hugetlb.usage_bytes, cli.units)
+            );
+        }
+
+        if let Some(zswap) = read_cgroup_zswap(&cgroup_path) {
+            let show = |bytes: Option<u64>| bytes.map_or("unknown".to_string(), |bytes| format_bytes(bytes, cli.units));
+            let writeback = match zswap.writeback_enabled {
+                Some(true) => ", writeback on",
+                Some(false) => ", writeback off",
+                None => "",
+            };
+            println!("    Zswap: {} used, limit {}{}",
+                show(zswap.current_bytes),
+                zswap.limit_bytes.map_or("unlimited".to_string(), |bytes| format_bytes(bytes, cli.units)),
+                writeback);
+        }
+
+        if cli.cgroup_path.is_none()
+            && let Some(count) = get_cgroup_process_count_for_path(&cgroup_path)
+            && count > 1
+        {
+            let others = count - 1;
+            let noun = if others == 1 { "process" } else { "processes" };
+            println!("    Note: you share this cgroup's limits with {} other {}", others, noun);
+        }
+
+        // Extra hint: detect if this looks like a default user.slice with no explicit limits
+        let looks_default_user = is_default_user_slice_path(&cgroup_path);
+        let explicit_limits = has_explicit_limits_at_path(&cgroup_path);
+        if looks_default_user && !explicit_limits {
+            println!("\n  Note: no explicit cpu/memory/cpuset limits detected at this cgroup; this looks like a default systemd user slice.");
+        }
+    }
+
+    let profile = cli.profile();
+    println!("\n  Profile: {}{}", profile.name(), if cli.profile.is_some() { " (--profile)" } else { " (detected)" });
+    if profile == profile::Profile::Container
+        && cpu_quota_for_cli(cli, &cgroup_path).is_none()
+        && memory_limit_for_cli(cli, &cgroup_path).is_none()
+    {
+        println!("  ⚠️  {}", CONTAINER_WITHOUT_LIMITS_WARNING);
+    }
+
+    // Show where the current user is allowed to configure limits
+    if let Some(root) = get_cgroup_delegation_root(&cgroup_path) {
+        println!("\n  Delegation Root: {}", root);
+        println!("    You can set limits at or below {}", root);
+    }
+}
+
+/// Counts the processes in a cgroup without exposing their PIDs.
+fn get_cgroup_process_count_for_path(cgroup_path: &str) -> Option<usize> {
+    procs::read_cgroup_pids(cgroup_path).map(|pids| pids.len())
+}
+
+/// Returns the parent of a cgroup path, or None for the root.
+fn parent_cgroup_path(cgroup_path: &str) -> Option<String> {
+    let trimmed = cgroup_path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return None;
+    }
+    match trimmed.rfind('/') {
+        Some(0) => Some("/".to_string()),
+        Some(idx) => Some(trimmed[..idx].to_string()),
+        None => None,
+    }
+}
+
+fn is_cgroup_dir_delegated(cgroup_path: &str) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Some(dir) = cgroupfs::mounts().v2_dir(cgroup_path) else {
+        return false;
+    };
+    let Ok(meta) = fs::metadata(&dir) else {
+        return false;
+    };
+    let Ok(c_dir) = std::ffi::CString::new(dir) else {
+        return false;
+    };
+    let uid = unsafe { libc::getuid() };
+    let writable = unsafe { libc::access(c_dir.as_ptr(), libc::W_OK) == 0 };
+    meta.uid() == uid && writable
+}
+
+/// Finds the topmost cgroup (cgroup v2 only) that is owned by and writable for
+/// the current uid, walking up from `cgroup_path`. This is the delegation
+/// boundary: limits can be configured at or below it.
+fn get_cgroup_delegation_root(cgroup_path: &str) -> Option<String> {
+    if cgroupfs::mounts().unified.is_none() || cgroup_path.is_empty() {
+        return None;
+    }
+    if !is_cgroup_dir_delegated(cgroup_path) {
+        return None;
+    }
+
+    let mut root = cgroup_path.to_string();
+    while let Some(parent) = parent_cgroup_path(&root) {
+        if !is_cgroup_dir_delegated(&parent) {
+            break;
+        }
+        root = parent;
+    }
+    Some(root)
+}
+
+/// Limits set on one cgroup, for `--compare-cgroup`.
+#[derive(Serialize, JsonSchema, Default)]
+struct CgroupLimits {
+    path: String,
+    /// False when the path is not a cgroup; every other field is then None
+    exists: bool,
+    cpu_quota: Option<f64>,
+    cpu_burst_usec: Option<u64>,
+    cpu_idle: Option<bool>,
+    cpuset_cpus: Option<String>,
+    memory_limit_bytes: Option<u64>,
+    effective_memory_limit_bytes: Option<u64>,
+    memory_high_bytes: Option<u64>,
+    memory_low_bytes: Option<u64>,
+    memory_min_bytes: Option<u64>,
+    swap_limit_bytes: Option<u64>,
+    process_count: Option<usize>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct CgroupComparison {
+    a: CgroupLimits,
+    b: CgroupLimits,
+    /// Names of the fields (other than `path`) whose values differ
+    differences: Vec<String>,
+}
+
+fn gather_cgroup_limits(cgroup_path: &str) -> CgroupLimits {
+    let path = match cgroup_path.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    };
+    if !cgroup_exists(path) {
+        return CgroupLimits {
+            path: path.to_string(),
+            ..CgroupLimits::default()
+        };
+    }
+    CgroupLimits {
+        path: path.to_string(),
+        exists: true,
+        cpu_quota: read_cpu_quota_at(path),
+        cpu_burst_usec: get_cgroup_cpu_burst_for_path(path),
+        cpu_idle: get_cgroup_cpu_idle_for_path(path),
+        cpuset_cpus: get_cgroup_cpuset_for_path(path).map(|cpus| cpulist::format_cpu_list(&cpus)),
+        memory_limit_bytes: get_cgroup_memory_limit_for_path(path),
+        effective_memory_limit_bytes: get_effective_memory_ceiling_for_path(path),
+        memory_high_bytes: read_cgroup_v2_bytes(path, "memory.high"),
+        memory_low_bytes: read_cgroup_v2_bytes(path, "memory.low"),
+        memory_min_bytes: read_cgroup_v2_bytes(path, "memory.min"),
+        swap_limit_bytes: get_cgroup_swap_limit_for_path(path),
+        process_count: get_cgroup_process_count_for_path(path),
+    }
+}
+
+fn compare_cgroups(path_a: &str, path_b: &str) -> CgroupComparison {
+    let a = gather_cgroup_limits(path_a);
+    let b = gather_cgroup_limits(path_b);
+    let (value_a, value_b) = (serde_json::to_value(&a).unwrap(), serde_json::to_value(&b).unwrap());
+    let differences = match (value_a, value_b) {
+        (serde_json::Value::Object(fields_a), serde_json::Value::Object(fields_b)) => fields_a
+            .iter()
+            .filter(|(name, value)| name.as_str() != "path" && fields_b.get(name.as_str()) != Some(value))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        _ => Vec::new(),
+    };
+    CgroupComparison { a, b, differences }
+}
+
+fn print_cgroup_comparison(cli: &Cli, path_a: &str, path_b: &str) {
+    let comparison = compare_cgroups(path_a, path_b);
+    let value = serde_json::to_value(&comparison).unwrap();
+    if cli.output_format() != OutputFormat::Text {
+        print!("{}", render_report(&value, cli.output_format()));
+        return;
+    }
+
+    let show = |name: &str, value: &serde_json::Value| match value {
+        serde_json::Value::Null => "-".to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Number(number) if name.ends_with("_bytes") => {
+            number.as_u64().map_or(number.to_string(), |bytes| format_bytes(bytes, cli.units))
+        }
+        other => other.to_string(),
+    };
+    let fields = |side: &str| value[side].as_object().cloned().unwrap_or_default();
+    let (fields_a, fields_b) = (fields("a"), fields("b"));
+    println!("  {:<30} {:<24} {}", "", comparison.a.path, comparison.b.path);
+    for (name, value_a) in fields_a.iter().filter(|(name, _)| name.as_str() != "path") {
+        let value_b = &fields_b[name.as_str()];
+        let marker = if comparison.differences.contains(name) { "*" } else { " " };
+        println!("{} {:<30} {:<24} {}", marker, name, show(name, value_a), show(name, value_b));
+    }
+    for side in [&comparison.a, &comparison.b] {
+        if !side.exists {
+            println!("⚠️  No cgroup at '{}' in the cgroup mounts", side.path);
+        }
+    }
+    match comparison.differences.len() {
+        0 => println!("No differences"),
+        count => println!("{} field(s) differ (marked *)", count),
+    }
+}
+
+fn print_top_consumers(cli: &Cli, cgroup_path: &str, count: usize) {
+    let location = if cgroup_path.is_empty() { "/" } else { cgroup_path };
+    let Some((total, top)) = procs::top_consumers(cgroup_path, count) else {
+        eprintln!("systemcheck: cannot read the process list of cgroup {}", location);
+        std::process::exit(1);
+    };
+    println!("Top memory consumers in {} ({} of {} processes):", location, top.len(), total);
+    println!("  {:>8}  {:>10}  COMMAND", "PID", "RSS");
+    for process in &top {
+        println!("  {:>8}  {:>10}  {}", process.pid, format_bytes(process.rss_bytes, cli.units), process.name);
+    }
+    if let Some(limit) = memory_limit_for_cli(cli, cgroup_path) {
+        let listed: u64 = top.iter().map(|process| process.rss_bytes).sum();
+        println!("Listed processes hold {} of the {} limit", format_bytes(listed, cli.units), format_bytes(limit, cli.units));
+    }
+}
+
+fn print_process_info(cli: &Cli) {
+    println!("Process Limits:");
+    println!("---------------");
+
+    let limits = rlimits::read_rlimits();
+    let mut any = false;
+    for (name, limit) in &limits {
+        if limit.soft.is_none() && limit.hard.is_none() {
+            continue;
+        }
+        any = true;
+        let show = |value: Option<u64>| match value {
+            None => "unlimited".to_string(),
+            Some(value) if rlimits::is_byte_valued(name) => format_bytes(value, cli.units),
+            Some(value) => value.to_string(),
+        };
+        println!("  {:<24} {} (hard {})", format!("RLIMIT_{}:", name.to_uppercase()), show(limit.soft), show(limit.hard));
+    }
+    if !any {
+        println!("  No finite resource limits");
+    }
+
+    let cgroup_path = target_cgroup_path(cli);
+    let memory_limit = memory_limit_for_cli(cli, &cgroup_path);
+    for warning in rlimit_warnings(&limits, memory_limit, cli.units) {
+        println!("  ⚠️  {}", warning);
+    }
+
+    let oom_score = read_trimmed("/proc/self/oom_score");
+    if let Some(adj) = read_trimmed("/proc/self/oom_score_adj") {
+        println!("  {:<24} {} (oom_score_adj {})", "OOM Score:", oom_score.as_deref().unwrap_or("unknown"), adj);
+        if adj.parse::<i64>().is_ok_and(|adj| adj >= OOM_SCORE_ADJ_NOTE) {
+            println!("  Note: this process is a preferred OOM-kill target");
+        }
+    }
+
+    let tunables = read_kernel_tunables();
+    if let Some(max) = tunables.max_map_count {
+        match tunables.process_map_count {
+            Some(count) => println!("  {:<24} {} of {} (vm.max_map_count)", "Memory Mappings:", count, max),
+            None => println!("  {:<24} {}", "vm.max_map_count:", max),
+        }
+    }
+    if let Some(warning) = map_count_warning(&tunables) {
+        println!("  ⚠️  {}", warning);
+    }
+}
+
+/// `oom_score_adj` at or above this makes the process a likely first victim
+/// (Kubernetes gives BestEffort pods 1000, Burstable up to 999).
+const OOM_SCORE_ADJ_NOTE: i64 = 500;
+
+/// Mappings above this fraction of `vm.max_map_count` get a warning.
+const MAP_COUNT_WARN_FRACTION: f64 = 0.80;
+
+fn read_kernel_tunables() -> KernelTunables {
+    KernelTunables {
+        max_map_count: read_trimmed("/proc/sys/vm/max_map_count").and_then(|value| value.parse().ok()),
+        process_map_count: probelog::read_to_string("/proc/self/maps")
+            .ok()
+            .map(|maps| maps.lines().count() as u64),
+    }
+}
+
+/// mmap fails with ENOMEM once a process reaches `vm.max_map_count`
+/// (65530 by default), which Elasticsearch, some JVMs and MPI stacks hit.
+fn map_count_warning(tunables: &KernelTunables) -> Option<String> {
+    let max = tunables.max_map_count?;
+    let count = tunables.process_map_count?;
+    if (count as f64) <= max as f64 * MAP_COUNT_WARN_FRACTION {
+        return None;
+    }
+    Some(format!(
+        "this process has {} memory mappings, over {:.0}% of vm.max_map_count ({}); further mmap calls will fail with ENOMEM",
+        count,
+        MAP_COUNT_WARN_FRACTION * 100.0,
+        max
+    ))
+}
+
+/// A finite RLIMIT_AS or RLIMIT_DATA below the cgroup memory limit makes
+/// allocations fail before the cgroup limit is reached, e.g. from `ulimit -v`
+/// in a login shell.
+fn rlimit_warnings(limits: &BTreeMap<String, rlimits::Rlimit>, memory_limit: Option<u64>, units: Units) -> Vec<String> {
+    let Some(memory_limit) = memory_limit else {
+        return Vec::new();
+    };
+    ["as", "data"]
+        .iter()
+        .filter_map(|name| {
+            let soft = limits.get(*name)?.soft?;
+            if soft >= memory_limit {
+                return None;
+            }
+            Some(format!(
+                "RLIMIT_{} is {}, below the {} cgroup memory limit; allocations past it fail with ENOMEM",
+                name.to_uppercase(),
+                format_bytes(soft, units),
+                format_bytes(memory_limit, units)
+            ))
+        })
+        .collect()
+}
+
+const HIDDEN_GPU_NOTE: &str =
+    "/dev/nvidia* device nodes exist but no GPU details are readable from sysfs or /proc/driver/nvidia; run nvidia-smi to inspect them";
+
+fn print_gpu_info() {
+    println!("GPU Information:");
+    println!("----------------");
+
+    let gpus = gpu::read_gpus();
+    if gpus.is_empty() {
+        if gpu::nvidia_device_nodes_present() {
+            println!("  Note: {}", HIDDEN_GPU_NOTE);
+        } else {
+            println!("  No GPUs found");
+        }
+        return;
+    }
+    println!("  GPUs Visible:            {}", gpus.len());
+    for gpu in &gpus {
+        println!("    {} {}", gpu.vendor, gpu.device);
+    }
+}
+
+fn print_disk_info(cli: &Cli) {
+    println!("Disk I/O Information:");
+    println!("---------------------");
+
+    if let Some(pressure) = psi::read_pressure("io", &target_cgroup_path(cli)) {
+        println!("  {:<25}{}", format!("I/O PSI ({}):", pressure.source), pressure.describe());
+    }
+
+    let disks = diskstats::read_diskstats();
+    if disks.is_empty() {
+        println!("  No block devices found in /proc/diskstats");
+        return;
+    }
+
+    for disk in &disks {
+        println!("  {}:", disk.device);
+        println!("    Reads:  {} completed, {} read, {} ms",
+            disk.reads_completed,
+            format_bytes(disk.sectors_read * 512, cli.units),
+            disk.read_time_ms);
+        println!("    Writes: {} completed, {} written, {} ms",
+            disk.writes_completed,
+            format_bytes(disk.sectors_written * 512, cli.units),
+            disk.write_time_ms);
+        println!("    Time doing I/O: {} ms", disk.io_time_ms);
+    }
+}
+
+fn is_default_user_slice_path(cgroup_path: &str) -> bool {
+    // Heuristic for systemd user sessions, e.g.: /user.slice/user-1000.slice/session-4.scope
+    cgroup_path.starts_with("/user.slice/user-") && cgroup_path.contains("/session-")
+}
+
+/// Renders text bold red when stdout is a terminal, unchanged otherwise.
+fn emphasize(text: &str) -> String {
+    use std::io::IsTerminal;
+    if std::io::stdout().is_terminal() {
+        format!("\x1b[1;31m{}\x1b[0m", text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    probelog::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn has_explicit_limits_at_path(cgroup_path: &str) -> bool {
+    has_explicit_limits_in(cgroupfs::mounts(), cgroup_path)
+}
+
+/// Each controller is checked in the hierarchy that handles it, so hybrid
+/// systems with cpu on v2 and memory on v1 are read correctly.
+fn has_explicit_limits_in(mounts: &cgroupfs::CgroupMounts, cgroup_path: &str) -> bool {
+    let read = |file: Option<String>| file.and_then(|path| read_trimmed(&path));
+    match mounts.hierarchy("cpu") {
+        // cpu.max at path set?
+        Some("v2") => {
+            if let Some(line) = read(mounts.v2_file(cgroup_path, "cpu.max"))
+                && let [quota, _period] = line.split_whitespace().collect::<Vec<_>>()[..]
+                && quota != "max"
+            {
+                return true;
+            }
+        }
+        _ => {
+            let quota = read(mounts.v1_file("cpu", cgroup_path, "cpu.cfs_quota_us"));
+            let period = read(mounts.v1_file("cpu", cgroup_path, "cpu.cfs_period_us"));
+            if let (Some(qs), Some(ps)) = (quota, period)
+                && let (Ok(q), Ok(p)) = (qs.parse::<i64>(), ps.parse::<i64>())
+                && q > 0
+                && p > 0
+            {
+                return true;
+            }
+        }
+    }
+    match mounts.hierarchy("memory") {
+        // memory.max at path set?
+        Some("v2") => {
+            if let Some(val) = read(mounts.v2_file(cgroup_path, "memory.max"))
+                && val != "max"
+            {
+                return true;
+            }
+        }
+        _ => {
+            if let Some(ls) = read(mounts.v1_file("memory", cgroup_path, "memory.limit_in_bytes"))
+                && let Ok(limit) = ls.parse::<u64>()
+                && !gather::is_v1_unlimited(limit)
+            {
+                return true;
+            }
+        }
+    }
+    // cpuset constrained?
+    let (path_cpuset, root_cpuset) = match mounts.hierarchy("cpuset") {
+        Some("v2") => (
+            read(mounts.v2_file(cgroup_path, "cpuset.cpus.effective")),
+            read(mounts.v2_file("/", "cpuset.cpus.effective")),
+        ),
+        _ => (
+            read(mounts.v1_file("cpuset", cgroup_path, "cpuset.cpus")),
+            read(mounts.v1_root_file("cpuset", "cpuset.cpus")),
+        ),
+    };
+    if let (Some(p), Some(r)) = (path_cpuset, root_cpuset)
+        && !p.is_empty()
+        && !r.is_empty()
+        && p != r
+    {
+        return true;
+    }
+    false
+}
+
+fn get_system_memory_from_proc() -> (u64, u64) {
+    system_memory_from(Path::new("/proc/meminfo"))
+}
+
+/// (MemTotal, MemAvailable) from `meminfo`, or from `sysinfo(2)` when it
+/// cannot be read (some restricted containers hide /proc/meminfo).
+fn system_memory_from(meminfo: &Path) -> (u64, u64) {
+    match probelog::read_to_string(meminfo) {
+        Ok(contents) => parse_meminfo_totals(&contents),
+        Err(_) => system_memory_from_sysinfo().unwrap_or((0, 0)),
+    }
+}
+
+/// (totalram, freeram) from `sysinfo(2)`. Less precise than meminfo: there is
+/// no MemAvailable, so reclaimable page cache counts as used and available
+/// memory is understated. Still better than reporting zero.
+fn system_memory_from_sysinfo() -> Option<(u64, u64)> {
+    let mut info: libc::sysinfo = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sysinfo(&mut info) } != 0 {
+        return None;
+    }
+    let unit = u64::from(info.mem_unit.max(1));
+    Some((info.totalram as u64 * unit, info.freeram as u64 * unit))
+}
+
+/// Returns (SwapTotal, SwapFree) in bytes; both 0 when there is no swap.
+fn get_system_swap_from_proc() -> (u64, u64) {
+    match probelog::read_to_string("/proc/meminfo") {
+        Ok(contents) => parse_meminfo_pair(&contents, "SwapTotal", "SwapFree"),
+        Err(_) => (0, 0),
+    }
+}
+
+/// Under `vm.overcommit_memory=2` large allocations fail once the commit
+/// charge reaches `CommitLimit`, however much RAM is free.
+fn commit_limit_warning(overcommit: &overcommit::Overcommit) -> Option<String> {
+    if !overcommit.near_commit_limit() {
+        return None;
+    }
+    Some(format!(
+        "overcommit is disabled (vm.overcommit_memory=2) and Committed_AS is above {:.0}% of CommitLimit; large allocations will fail even with free RAM",
+        overcommit::COMMIT_WARN_FRACTION * 100.0
+    ))
+}
+
+/// Huge page pools above this fraction of MemTotal get an explanatory note.
+const HUGEPAGE_NOTE_FRACTION: f64 = 0.05;
+
+/// Unreclaimable slab above this fraction of MemTotal gets a breakdown line.
+const SLAB_NOTE_FRACTION: f64 = 0.05;
+
+/// Dirty pages above this fraction of MemTotal get a writeback warning.
+const DIRTY_WARN_FRACTION: f64 = 0.10;
+
+/// MemAvailable below this fraction of MemTotal counts as low memory.
+const LOW_MEMORY_FRACTION: f64 = 0.10;
+
+/// "Swap: 8 GiB total, 7.2 GiB free" or "Swap: none".
+fn swap_line(units: Units) -> String {
+    match get_system_swap_from_proc() {
+        (0, _) => "Swap: none".to_string(),
+        (total, free) => format!("Swap: {} total, {} free", format_bytes(total, units), format_bytes(free, units)),
+    }
+}
+
+/// One `/proc/meminfo` value in bytes; None when the key is absent.
+fn read_meminfo_value(key: &str) -> Option<u64> {
+    read_meminfo_value_in(Path::new("/proc/meminfo"), key)
+}
+
+/// `read_meminfo_value` against any meminfo-format file.
+fn read_meminfo_value_in(meminfo: &Path, key: &str) -> Option<u64> {
+    let contents = probelog::read_to_string(meminfo).ok()?;
+    parse_meminfo_keys(&contents, &[key]).get(key).copied()
+}
+
+/// Returns (MemTotal, MemAvailable) in bytes; keys are matched case-insensitively.
+/// MemAvailable is estimated on kernels that predate it (see `estimate_mem_available`).
+fn parse_meminfo_totals(contents: &str) -> (u64, u64) {
+    let (total, available) = parse_meminfo_pair(contents, "MemTotal", "MemAvailable");
+    if available == 0
+        && !meminfo_has_available(contents)
+        && let Some(estimate) = estimate_mem_available(contents)
+    {
+        return (total, estimate);
+    }
+    (total, available)
+}
+
+fn mem_available_estimated() -> bool {
+    probelog::read_to_string("/proc/meminfo")
+        .is_ok_and(|contents| !meminfo_has_available(&contents) && estimate_mem_available(&contents).is_some())
+}
+
+/// Whether the kernel reports `MemAvailable` at all (added in 3.14).
+fn meminfo_has_available(contents: &str) -> bool {
+    contents
+        .lines()
+        .any(|line| line.split_once(':').is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("MemAvailable")))
+}
+
+/// MemFree + Buffers + Cached - Shmem: a rough stand-in for MemAvailable on
+/// old kernels. It ignores reclaimable slab and the watermarks the kernel
+/// subtracts, so it is an estimate, not the real figure. None without MemFree.
+fn estimate_mem_available(contents: &str) -> Option<u64> {
+    let values = parse_meminfo_keys(contents, &["MemFree", "Buffers", "Cached", "Shmem"]);
+    let value = |key: &str| values.get(key).copied().unwrap_or(0);
+    let free = *values.get("MemFree")?;
+    Some((free + value("Buffers") + value("Cached")).saturating_sub(value("Shmem")))
+}
+
+/// Returns the values of two `/proc/meminfo` keys in bytes (0 when missing).
+fn parse_meminfo_pair(contents: &str, first_key: &str, second_key: &str) -> (u64, u64) {
+    let values = parse_meminfo_keys(contents, &[first_key, second_key]);
+    (values.get(first_key).copied().unwrap_or(0), values.get(second_key).copied().unwrap_or(0))
+}
+
+/// The `wanted` `/proc/meminfo` keys that are present, in bytes, keyed as
+/// spelled in `wanted`. Keys match case-insensitively; lines with an
+/// unexpected unit are skipped.
+fn parse_meminfo_keys<'a>(contents: &str, wanted: &[&'a str]) -> HashMap<&'a str, u64> {
+    contents
+        .lines()
+        .filter_map(parse_meminfo_line)
+        .filter_map(|(key, bytes)| {
+            let wanted_key = wanted.iter().find(|wanted_key| key.eq_ignore_ascii_case(wanted_key))?;
+            Some((*wanted_key, bytes))
+        })
+        .collect()
+}
+
+/// Parses a `/proc/meminfo` line into its key and value in bytes. The unit
+/// must be kilobytes (`kB` in any casing) or absent, in which case kilobytes
+/// are assumed as in the kernel's own format. Lines with any other unit are
+/// rejected rather than converted to a wrong byte count.
+fn parse_meminfo_line(line: &str) -> Option<(&str, u64)> {
+    let (key, rest) = line.split_once(':')?;
+    let mut parts = rest.split_whitespace();
+    let value = parts.next()?.parse::<u64>().ok()?;
+    match parts.next() {
+        None => Some((key.trim(), value.checked_mul(1024)?)),
+        Some(unit) if unit.eq_ignore_ascii_case("kB") => Some((key.trim(), value.checked_mul(1024)?)),
+        Some(_) => None,
+    }
+}
+
+fn read_kernel_memory() -> KernelMemory {
+    probelog::read_to_string("/proc/meminfo").map(|contents| parse_kernel_memory(&contents)).unwrap_or_default()
+}
+
+fn parse_kernel_memory(contents: &str) -> KernelMemory {
+    let values = parse_meminfo_keys(contents, &["Slab", "SReclaimable", "SUnreclaim", "KernelStack", "PageTables"]);
+    KernelMemory {
+        slab_bytes: values.get("Slab").copied(),
+        slab_reclaimable_bytes: values.get("SReclaimable").copied(),
+        slab_unreclaimable_bytes: values.get("SUnreclaim").copied(),
+        kernel_stack_bytes: values.get("KernelStack").copied(),
+        page_tables_bytes: values.get("PageTables").copied(),
+    }
+}
+
+fn read_page_cache() -> PageCache {
+    probelog::read_to_string("/proc/meminfo").map(|contents| parse_page_cache(&contents)).unwrap_or_default()
+}
+
+fn parse_page_cache(contents: &str) -> PageCache {
+    let values = parse_meminfo_keys(contents, &["Buffers", "Cached", "Dirty", "Writeback"]);
+    PageCache {
+        buffers_bytes: values.get("Buffers").copied(),
+        cached_bytes: values.get("Cached").copied(),
+        dirty_bytes: values.get("Dirty").copied(),
+        writeback_bytes: values.get("Writeback").copied(),
+    }
+}
+
+/// Dirty pages above `DIRTY_WARN_FRACTION` of MemTotal.
+fn dirty_pages_warning(page_cache: &PageCache, system_total: u64, units: Units) -> Option<String> {
+    let dirty = page_cache.dirty_bytes?;
+    if system_total == 0 || (dirty as f64) <= system_total as f64 * DIRTY_WARN_FRACTION {
+        return None;
+    }
+    Some(format!(
+        "{} of dirty pages ({:.0}% of RAM) are waiting for writeback; expect a burst of blocking I/O when they are flushed",
+        format_bytes(dirty, units),
+        dirty as f64 / system_total as f64 * 100.0
+    ))
+}
+
+/// Base page size in bytes.
+fn get_page_size() -> Option<u64> {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    u64::try_from(size).ok().filter(|&size| size > 0)
+}
+
+/// Number of CPUs present in the system (online or not).
+fn get_system_cpu_count() -> usize {
+    // Compact range list such as "0-63"; cheap even on very large machines
+    if let Some(count) = read_cpu_list_count("/sys/devices/system/cpu/present") {
+        return count;
+    }
+
+    // Fall back to counting "processor" lines in /proc/cpuinfo
+    if let Ok(contents) = probelog::read_to_string("/proc/cpuinfo") {
+        let count = contents
+            .lines()
+            .filter(|line| line.starts_with("processor"))
+            .count();
+        if count > 0 {
+            return count;
+        }
+    }
+
+    // Fallback to sysconf if available
+    unsafe {
+        let count = libc::sysconf(libc::_SC_NPROCESSORS_ONLN);
+        if count > 0 {
+            return count as usize;
+        }
+    }
+
+    // Last resort: use num_cpus (which may be cgroup limited)
+    num_cpus::get()
+}
+
+/// Number of CPUs currently online.
+fn get_system_online_cpu_count() -> usize {
+    if let Some(count) = read_cpu_list_count("/sys/devices/system/cpu/online") {
+        return count;
+    }
+
+    unsafe {
+        let count = libc::sysconf(libc::_SC_NPROCESSORS_ONLN);
+        if count > 0 {
+            return count as usize;
+        }
+    }
+
+    num_cpus::get()
+}
+
+fn read_cpu_list_count(path: &str) -> Option<usize> {
+    let count = cpulist::parse_cpu_list(&probelog::read_to_string(path).ok()?).len();
+    if count > 0 { Some(count) } else { None }
+}
+
+fn get_system_physical_cpu_count() -> usize {
+    // Prefer the sysfs topology, which exists on every architecture
+    if let Some(count) = count_physical_cores_sysfs(Path::new("/sys/devices/system/cpu")) {
+        return count;
+    }
+
+    // Fall back to physical cores from /proc/cpuinfo (x86 only has these lines)
+    if let Ok(contents) = probelog::read_to_string("/proc/cpuinfo") {
+        let mut core_ids = HashSet::new();
+        let mut current_physical_id = None;
+
+        for line in contents.lines() {
+            if line.starts_with("physical id") {
+                current_physical_id = line.split(':')
+                    .nth(1)
+                    .and_then(|s| s.trim().parse::<usize>().ok());
+            } else if line.starts_with("core id")
+                && let Some(phys_id) = current_physical_id
+                && let Some(core_id) = line.split(':')
+                    .nth(1)
+                    .and_then(|s| s.trim().parse::<usize>().ok())
+            {
+                core_ids.insert((phys_id, core_id));
+            }
+        }
+
+        if !core_ids.is_empty() {
+            return core_ids.len();
+        }
+    }
+
+    // Fallback: use num_cpus for physical cores
+    num_cpus::get_physical()
+}
+
+/// Counts physical cores from `cpu*/topology` under `sys_cpu`. A core is
+/// identified by its `core_cpus_list` (or the older `thread_siblings_list`),
+/// falling back to the (physical_package_id, core_id) pair; `core_id` alone
+/// repeats across ARM64 clusters. Returns None when no topology is exposed.
+fn count_physical_cores_sysfs(sys_cpu: &Path) -> Option<usize> {
+    let mut cores = HashSet::new();
+    for entry in fs::read_dir(sys_cpu).ok()?.flatten() {
+        let name = entry.file_name();
+        let is_cpu_dir = name
+            .to_string_lossy()
+            .strip_prefix("cpu")
+            .map(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or(false);
+        if !is_cpu_dir {
+            continue;
+        }
+
+        let topology = entry.path().join("topology");
+        let read = |file: &str| probelog::read_to_string(topology.join(file)).ok().map(|s| s.trim().to_string());
+        if let Some(siblings) = read("core_cpus_list").or_else(|| read("thread_siblings_list")) {
+            cores.insert(siblings);
+        } else if let (Some(package), Some(core)) = (read("physical_package_id"), read("core_id")) {
+            cores.insert(format!("{}:{}", package, core));
+        }
+    }
+
+    if cores.is_empty() { None } else { Some(cores.len()) }
+}
+
+/// The cgroup being reported on: `--cgroup-path` when given, else our own.
+fn target_cgroup_path(cli: &Cli) -> String {
+    cli.cgroup_path.clone().unwrap_or_else(get_current_cgroup_path)
+}
+
+fn get_current_cgroup_path() -> String {
+    resolve_current_cgroup_path().0
+}
+
+/// How the cgroup under report was found: "cli" for `--cgroup-path`, else
+/// the strategy from `resolve_cgroup_path`.
+fn cgroup_path_resolution(cli: &Cli) -> String {
+    if cli.cgroup_path.is_some() {
+        "cli".to_string()
+    } else {
+        resolve_current_cgroup_path().1.to_string()
+    }
+}
+
+/// Our cgroup from `/proc/self/cgroup`, mapped onto the cgroup mounts.
+fn resolve_current_cgroup_path() -> (String, &'static str) {
+    let path = probelog::read_to_string("/proc/self/cgroup")
+        .map(|contents| cgroupfs::parse_current_cgroup_path(&contents))
+        .unwrap_or_default();
+    let mount_root = cgroup_mount_root(&mountinfo::read_mounts());
+    let resolved = resolve_cgroup_path(&path, mount_root.as_deref(), cgroup_exists);
+    cgroupfs::mounts().set_own_cgroup(&resolved.0);
+    resolved
+}
+
+/// The root (mountinfo field 4) of the cgroup2 mount, or of the v1 memory
+/// hierarchy. Inside a container without a cgroup namespace this is the
+/// container's host-side cgroup, e.g. "/docker/<id>".
+fn cgroup_mount_root(mounts: &[mountinfo::Mount]) -> Option<String> {
+    mounts
+        .iter()
+        .find(|mount| mount.fstype == "cgroup2")
+        .or_else(|| {
+            mounts
+                .iter()
+                .find(|mount| mount.fstype == "cgroup" && mount.super_options.split(',').any(|option| option == "memory"))
+        })
+        .map(|mount| mount.root.clone())
+}
+
+/// Without a cgroup namespace `/proc/self/cgroup` shows the host path
+/// ("/docker/<id>/app") while `/sys/fs/cgroup` is mounted at that cgroup, so
+/// the path does not exist as given. Tries it as is ("direct"), then
+/// successively shorter suffixes ("suffix"), then the path relative to the
+/// cgroup mount's root ("mount-root"). Falls back to the path as given
+/// ("unresolved") so the usual missing-file handling applies.
+fn resolve_cgroup_path(path: &str, mount_root: Option<&str>, exists: impl Fn(&str) -> bool) -> (String, &'static str) {
+    if exists(path) {
+        return (path.to_string(), "direct");
+    }
+    let components: Vec<&str> = path.split('/').filter(|component| !component.is_empty()).collect();
+    for start in 1..components.len() {
+        let suffix = format!("/{}", components[start..].join("/"));
+        if exists(&suffix) {
+            return (suffix, "suffix");
+        }
+    }
+    if let Some(root) = mount_root.map(|root| root.trim_end_matches('/'))
+        && !root.is_empty()
+        && let Some(rest) = path.strip_prefix(root)
+        && (rest.is_empty() || rest.starts_with('/'))
+    {
+        let relative = if rest.is_empty() { "/" } else { rest };
+        if exists(relative) {
+            return (relative.to_string(), "mount-root");
+        }
+    }
+    (path.to_string(), "unresolved")
+}
+
+fn read_controller_paths() -> BTreeMap<String, String> {
+    probelog::read_to_string("/proc/self/cgroup")
+        .map(|contents| cgroupfs::parse_controller_paths(&contents))
+        .unwrap_or_default()
+}
+
+/// CPU quota for `cgroup_path`, honouring `--no-fallback-to-root`.
+fn cpu_quota_for_cli(cli: &Cli, cgroup_path: &str) -> Option<f64> {
+    if cli.no_fallback_to_root {
+        read_cpu_quota_at(cgroup_path)
+    } else {
+        get_cgroup_cpu_quota_for_path(cgroup_path)
+    }
+}
+
+/// Memory limit that applies to `cgroup_path`: the lowest one in the
+/// hierarchy, or with `--no-fallback-to-root` only the cgroup's own.
+fn memory_limit_for_cli(cli: &Cli, cgroup_path: &str) -> Option<u64> {
+    if cli.no_fallback_to_root {
+        read_memory_limit_at(cgroup_path)
+    } else {
+        get_effective_memory_ceiling_for_path(cgroup_path)
+    }
+}
+
+/// CPU quota set at exactly this cgroup level (no root fallback).
+fn read_cpu_quota_at(cgroup_path: &str) -> Option<f64> {
+    gather::read_cpu_quota(cgroup_path).ok().flatten()
+}
+
+fn get_cgroup_cpu_quota_for_path(cgroup_path: &str) -> Option<f64> {
+    get_cgroup_cpu_quota_in(cgroupfs::mounts(), cgroup_path)
+}
+
+fn get_cgroup_cpu_quota_in(mounts: &cgroupfs::CgroupMounts, cgroup_path: &str) -> Option<f64> {
+    // Try cgroup v2 first, unless a v1 mount holds the cpu controller
+    if mounts.hierarchy("cpu") != Some("v1")
+        && let Some(quota) = read_cgroup_v2_cpu_quota_for_path(mounts, cgroup_path)
+    {
+        return Some(quota);
+    }
+
+    // Fall back to cgroup v1
+    read_cgroup_v1_cpu_quota_for_path(mounts, cgroup_path)
+}
+
+/// v2 quota for `cgroup_path`, else the root cgroup's.
+fn read_cgroup_v2_cpu_quota_for_path(mounts: &cgroupfs::CgroupMounts, cgroup_path: &str) -> Option<f64> {
+    [cgroup_path, "/"].into_iter().find_map(|path| {
+        let cpu_max = mounts.v2_file(path, "cpu.max")?;
+        let contents = probelog::read_to_string(&cpu_max).ok()?;
+        gather::parse_cpu_max(&cpu_max, &contents).ok().flatten()
+    })
+}
+
+/// A v1 `cpu.cfs_quota_us` reading at one cgroup level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum V1CpuQuota {
+    Limited(f64),
+    /// An explicit -1: deliberately unlimited at this level
+    Unlimited,
+    /// Missing or malformed files
+    Unreadable,
+}
+
+fn read_v1_cpu_quota_in(cpu_root: &Path, cgroup_path: &str) -> V1CpuQuota {
+    let dir = cpu_root.join(cgroup_path.trim_start_matches('/'));
+    match gather::read_cfs_quota(&dir.to_string_lossy()) {
+        Ok(Some(Some(quota))) => V1CpuQuota::Limited(quota),
+        Ok(Some(None)) => V1CpuQuota::Unlimited,
+        _ => V1CpuQuota::Unreadable,
+    }
+}
+
+/// v1 quota for `cgroup_path` under the cpu hierarchy at `cpu_root`. Only an
+/// unreadable quota falls back to the root cgroup; an explicit -1 is final.
+fn v1_cpu_quota_with_fallback(cpu_root: &Path, cgroup_path: &str, fallback_to_root: bool) -> Option<f64> {
+    match read_v1_cpu_quota_in(cpu_root, cgroup_path) {
+        V1CpuQuota::Limited(quota) => Some(quota),
+        V1CpuQuota::Unlimited => None,
+        V1CpuQuota::Unreadable if fallback_to_root => match read_v1_cpu_quota_in(cpu_root, "/") {
+            V1CpuQuota::Limited(quota) => Some(quota),
+            _ => None,
+        },
+        V1CpuQuota::Unreadable => None,
+    }
+}
+
+fn read_cgroup_v1_cpu_quota_for_path(mounts: &cgroupfs::CgroupMounts, cgroup_path: &str) -> Option<f64> {
+    v1_cpu_quota_with_fallback(Path::new(mounts.v1_root("cpu")?), mounts.v1_path("cpu", cgroup_path), true)
+}
+
+/// Memory limit configured on the cgroup itself. A limit inherited from a
+/// parent slice is only visible through `effective_memory_limit_for_path`.
+fn get_cgroup_memory_limit_for_path(cgroup_path: &str) -> Option<u64> {
+    get_cgroup_memory_limit_in(cgroupfs::mounts(), cgroup_path)
+}
+
+fn get_cgroup_memory_limit_in(mounts: &cgroupfs::CgroupMounts, cgroup_path: &str) -> Option<u64> {
+    gather::read_memory_limit_in(mounts, cgroup_path).ok().flatten()
+}
+
+/// pids.max set at exactly this cgroup level; None when "max" or unavailable.
+fn get_cgroup_pids_limit_for_path(cgroup_path: &str) -> Option<u64> {
+    let mounts = cgroupfs::mounts();
+    [mounts.v2_file(cgroup_path, "pids.max"), mounts.v1_file("pids", cgroup_path, "pids.max")]
+        .into_iter()
+        .flatten()
+        .find_map(|path| read_trimmed(&path))
+        .and_then(|value| value.parse().ok())
+}
+
+fn get_cgroup_memory_usage_for_path(cgroup_path: &str) -> Option<u64> {
+    let mounts = cgroupfs::mounts();
+    [
+        mounts.v2_file(cgroup_path, "memory.current"),
+        mounts.v2_file("/", "memory.current"),
+        mounts.v1_file("memory", cgroup_path, "memory.usage_in_bytes"),
+        mounts.v1_root_file("memory", "memory.usage_in_bytes"),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|path| read_trimmed(&path)?.parse::<u64>().ok())
+}
+
+/// Reads `file` of `cgroup_path` in the cgroup2 hierarchy.
+fn read_cgroup_v2_file(cgroup_path: &str, file: &str) -> Option<String> {
+    read_trimmed(&cgroupfs::mounts().v2_file(cgroup_path, file)?)
+}
+
+/// Reads `file` of `cgroup_path` in the v1 hierarchy holding `controller`.
+fn read_cgroup_v1_file(controller: &str, cgroup_path: &str, file: &str) -> Option<String> {
+    read_trimmed(&cgroupfs::mounts().v1_file(controller, cgroup_path, file)?)
+}
+
+/// Applies `read` to `cgroup_path` and each of its ancestors, returning the
+/// smallest value found.
+fn min_over_cgroup_ancestors(cgroup_path: &str, read: impl Fn(&str) -> Option<u64>) -> Option<u64> {
+    let mut current = Some(cgroup_path.to_string()).filter(|p| !p.is_empty());
+    let mut min: Option<u64> = None;
+    while let Some(path) = current {
+        if let Some(value) = read(&path) {
+            min = Some(min.map_or(value, |m| m.min(value)));
+        }
+        current = parent_cgroup_path(&path);
+    }
+    min
+}
+
+/// Memory limit set at exactly this cgroup level (no root fallback).
+fn read_memory_limit_at(cgroup_path: &str) -> Option<u64> {
+    gather::read_memory_limit(cgroup_path).ok().flatten()
+}
+
+/// Reads a v2 byte-valued file at exactly this cgroup level; "max" maps to None.
+fn read_cgroup_v2_bytes(cgroup_path: &str, file: &str) -> Option<u64> {
+    read_cgroup_v2_file(cgroup_path, file).and_then(|value| value.parse::<u64>().ok())
+}
+
+fn read_cgroup_zswap(cgroup_path: &str) -> Option<ZswapInfo> {
+    read_cgroup_zswap_in(Path::new(&cgroupfs::mounts().v2_dir(cgroup_path)?))
+}
 
-    // CGroup summary note
-    let looks_default_user = is_default_user_slice_path(&cgroup_path);
-    let explicit_limits = has_explicit_limits_at_path(&cgroup_path);
-    if looks_default_user && !explicit_limits {
-        println!("CGroup: default user slice (no explicit limits)");
-    } else if !cgroup_path.is_empty() && cgroup_path != "/" {
-        if explicit_limits {
-            println!("CGroup: limits present at {}", cgroup_path);
-        } else {
-            println!("CGroup: {} (no explicit limits)", cgroup_path);
-        }
-    }
-    println!("\nsee more details with systemcheck -v");
+/// None unless `memory.zswap.max` exists; most kernels are built without
+/// per-cgroup zswap.
+fn read_cgroup_zswap_in(dir: &Path) -> Option<ZswapInfo> {
+    let read = |file: &str| probelog::read_to_string(dir.join(file)).ok().map(|value| value.trim().to_string());
+    let max = read("memory.zswap.max")?;
+    Some(ZswapInfo {
+        limit_bytes: max.parse().ok(),
+        current_bytes: read("memory.zswap.current").and_then(|value| value.parse().ok()),
+        writeback_enabled: read("memory.zswap.writeback").map(|value| value == "1"),
+    })
 }
 
-fn print_cpu_info() {
-    println!("CPU Information:");
-    println!("----------------");
+/// Swap limit set at exactly this cgroup level. v1 only limits RAM+swap
+/// together, so the swap share is memsw minus the RAM limit.
+fn get_cgroup_swap_limit_for_path(cgroup_path: &str) -> Option<u64> {
+    if let Some(value) = read_cgroup_v2_file(cgroup_path, "memory.swap.max") {
+        return value.parse::<u64>().ok();
+    }
+    let memsw = read_cgroup_v1_file("memory", cgroup_path, "memory.memsw.limit_in_bytes")?
+        .parse::<u64>()
+        .ok()
+        .filter(|&limit| !gather::is_v1_unlimited(limit))?;
+    let ram = read_memory_limit_at(cgroup_path)?;
+    Some(memsw.saturating_sub(ram))
+}
 
-    // Get actual system CPUs (not limited by cgroups)
-    let system_logical_cpus = get_system_cpu_count();
-    let system_physical_cpus = get_system_physical_cpu_count();
+fn get_cgroup_oom_group_for_path(cgroup_path: &str) -> Option<bool> {
+    read_cgroup_v2_file(cgroup_path, "memory.oom.group").map(|value| value == "1")
+}
 
-    // Get cgroup-limited CPUs
-    let available_cpus = num_cpus::get();
+/// v1 `memory.swappiness`; the file does not exist on cgroup v2.
+fn get_cgroup_swappiness_for_path(cgroup_path: &str) -> Option<u64> {
+    read_cgroup_v1_file("memory", cgroup_path, "memory.swappiness").and_then(|value| value.parse::<u64>().ok())
+}
 
-    println!("  System Logical CPUs:     {} threads", system_logical_cpus);
-    println!("  System Physical CPUs:    {} cores", system_physical_cpus);
-    println!("  Available CPUs (cgroup): {}", available_cpus);
+fn get_cgroup_swap_usage_for_path(cgroup_path: &str) -> Option<u64> {
+    if let Some(value) = read_cgroup_v2_file(cgroup_path, "memory.swap.current") {
+        return value.parse::<u64>().ok();
+    }
+    let v1 = |file: &str| read_cgroup_v1_file("memory", cgroup_path, file).and_then(|value| value.parse::<u64>().ok());
+    Some(v1("memory.memsw.usage_in_bytes")?.saturating_sub(v1("memory.usage_in_bytes")?))
+}
 
-    if available_cpus < system_logical_cpus {
-        println!("  ⚠️  CPU is constrained by cgroups to {} of {} system CPUs",
-                 available_cpus, system_logical_cpus);
+/// The smallest memory limit on `cgroup_path` or any ancestor, and the cgroup
+/// that sets it. On a tie the level closest to `cgroup_path` wins.
+fn effective_memory_limit_for_path(cgroup_path: &str) -> Option<(u64, String)> {
+    let mut current = Some(cgroup_path.to_string()).filter(|p| !p.is_empty());
+    let mut min: Option<(u64, String)> = None;
+    while let Some(path) = current {
+        if let Some(limit) = read_memory_limit_at(&path)
+            && min.as_ref().is_none_or(|(lowest, _)| limit < *lowest)
+        {
+            min = Some((limit, path.clone()));
+        }
+        current = parent_cgroup_path(&path);
     }
+    min
+}
 
-    if let Some(cpu_quota) = get_cgroup_cpu_quota() {
-        println!("  CGroup CPU Quota:        {:.2} CPUs", cpu_quota);
+fn get_effective_memory_ceiling_for_path(cgroup_path: &str) -> Option<u64> {
+    effective_memory_limit_for_path(cgroup_path).map(|(limit, _)| limit)
+}
+
+fn get_effective_memory_swap_ceiling_for_path(cgroup_path: &str) -> Option<u64> {
+    if cgroupfs::mounts().hierarchy("memory") == Some("v2") {
+        let ram = get_effective_memory_ceiling_for_path(cgroup_path)?;
+        let swap = min_over_cgroup_ancestors(cgroup_path, |path| {
+            read_cgroup_v2_file(path, "memory.swap.max").and_then(|value| value.parse::<u64>().ok())
+        })?;
+        return Some(ram.saturating_add(swap));
     }
+
+    // v1: memsw is already the combined RAM+swap limit
+    min_over_cgroup_ancestors(cgroup_path, |path| {
+        read_cgroup_v1_file("memory", path, "memory.memsw.limit_in_bytes")
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|&limit| !gather::is_v1_unlimited(limit))
+    })
 }
 
-fn print_memory_info() {
-    println!("Memory Information:");
-    println!("-------------------");
+/// Parses a flat-keyed cgroup file such as `memory.stat` or `cpu.stat`
+/// ("key value" per line).
+fn parse_flat_keyed(contents: &str) -> HashMap<String, u64> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let key = parts.next()?;
+            let value = parts.next()?.parse::<u64>().ok()?;
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
 
-    // Get real system memory from /proc/meminfo
-    let (system_total, system_available) = get_system_memory_from_proc();
+/// Reads cgroup v2 `cpu.max.burst` (microseconds). Zero means no burst, so
+/// it is reported as None like an absent file.
+fn get_cgroup_cpu_burst_for_path(cgroup_path: &str) -> Option<u64> {
+    read_cgroup_v2_file(cgroup_path, "cpu.max.burst")
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|&burst| burst > 0)
+}
 
-    println!("  System Total Memory:     {}", humanize_bytes_binary!(system_total));
-    println!("  System Available Memory: {}", humanize_bytes_binary!(system_available));
+/// CPU ids in the cgroup's effective cpuset (v2, then v1).
+fn get_cgroup_cpuset_for_path(cgroup_path: &str) -> Option<Vec<u32>> {
+    let mounts = cgroupfs::mounts();
+    let candidates = [
+        mounts.v2_file(cgroup_path, "cpuset.cpus.effective"),
+        mounts.v1_file("cpuset", cgroup_path, "cpuset.effective_cpus"),
+        mounts.v1_file("cpuset", cgroup_path, "cpuset.cpus"),
+    ];
+    candidates.iter().flatten().find_map(|path| {
+        let cpus = cpulist::parse_cpu_list(&read_trimmed(path)?);
+        if cpus.is_empty() { None } else { Some(cpus) }
+    })
+}
 
-    let system_used = system_total.saturating_sub(system_available);
-    println!("  System Used Memory:      {}", humanize_bytes_binary!(system_used));
+/// Number of CPUs in the cgroup's effective cpuset.
+fn get_cgroup_cpuset_count_for_path(cgroup_path: &str) -> Option<usize> {
+    get_cgroup_cpuset_for_path(cgroup_path).map(|cpus| cpus.len())
+}
 
-    // Get the current cgroup path and check its memory limit
-    let cgroup_path = get_current_cgroup_path();
+/// Reads `cpu.idle` at exactly this cgroup level; None on v1 or older kernels.
+fn get_cgroup_cpu_idle_for_path(cgroup_path: &str) -> Option<bool> {
+    match read_cgroup_v2_file(cgroup_path, "cpu.idle")?.as_str() {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
 
-    if let Some(cgroup_limit) = get_cgroup_memory_limit_for_path(&cgroup_path) {
-        println!("  CGroup Memory Limit:     {}", humanize_bytes_binary!(cgroup_limit));
+fn get_cgroup_cpu_throttling_for_path(cgroup_path: &str) -> Option<CpuThrottleStats> {
+    // Try cgroup v2 with path, then v1
+    let mounts = cgroupfs::mounts();
+    if let Some(v2_path) = mounts.v2_file(cgroup_path, "cpu.stat")
+        && let Some(stats) = probelog::read_to_string(&v2_path).ok().and_then(|contents| parse_cpu_throttling(&contents, false))
+    {
+        return Some(stats);
+    }
+    let v1_path = mounts.v1_file("cpu", cgroup_path, "cpu.stat")?;
+    probelog::read_to_string(&v1_path).ok().and_then(|contents| parse_cpu_throttling(&contents, true))
+}
 
-        if cgroup_limit < system_total {
-            println!("  ⚠️  Memory is constrained by cgroups!");
+/// v2 `cpu.stat` counts time in `*_usec`; v1 uses `throttled_time` and
+/// `burst_time` in nanoseconds.
+fn parse_cpu_throttling(contents: &str, v1: bool) -> Option<CpuThrottleStats> {
+    let stat = parse_flat_keyed(contents);
+    let usec = |v2_key: &str, v1_key: &str| {
+        if v1 { stat.get(v1_key).map(|ns| ns / 1000) } else { stat.get(v2_key).copied() }
+    };
+    Some(CpuThrottleStats {
+        nr_periods: *stat.get("nr_periods")?,
+        nr_throttled: *stat.get("nr_throttled")?,
+        throttled_usec: usec("throttled_usec", "throttled_time").unwrap_or(0),
+        nr_bursts: stat.get("nr_bursts").copied(),
+        burst_usec: usec("burst_usec", "burst_time"),
+    })
+}
 
-            if let Some(current_usage) = get_cgroup_memory_usage_for_path(&cgroup_path) {
-                let usage_percent = (current_usage as f64 / cgroup_limit as f64) * 100.0;
-                println!("  CGroup Memory Usage:     {} ({:.1}% of limit)",
-                    humanize_bytes_binary!(current_usage), usage_percent);
-            }
+fn get_cgroup_cpu_usage_for_path(cgroup_path: &str) -> Option<CgroupCpuUsage> {
+    // Try cgroup v2 with path
+    if let Some(v2_path) = cgroupfs::mounts().v2_file(cgroup_path, "cpu.stat")
+        && let Ok(contents) = probelog::read_to_string(&v2_path)
+    {
+        let stat = parse_flat_keyed(&contents);
+        if let Some(&usage_usec) = stat.get("usage_usec") {
+            return Some(CgroupCpuUsage {
+                source: "cpu.stat",
+                usage_usec,
+                user_usec: stat.get("user_usec").copied(),
+                system_usec: stat.get("system_usec").copied(),
+            });
         }
     }
+
+    get_cgroup_cpuacct_usage_for_path(cgroup_path).map(|usage_usec| CgroupCpuUsage {
+        source: "cpuacct.usage",
+        usage_usec,
+        user_usec: read_cpuacct_usec(cgroup_path, "cpuacct.usage_user"),
+        system_usec: read_cpuacct_usec(cgroup_path, "cpuacct.usage_sys"),
+    })
 }
 
-fn print_cgroup_info() {
-    println!("CGroup Information:");
-    println!("-------------------");
+/// Cumulative CPU time of a v1 cgroup from `cpuacct.usage`, in microseconds
+/// (the file itself counts nanoseconds).
+fn get_cgroup_cpuacct_usage_for_path(cgroup_path: &str) -> Option<u64> {
+    read_cpuacct_usec(cgroup_path, "cpuacct.usage")
+}
 
-    let cgroup_v2 = Path::new("/sys/fs/cgroup/cgroup.controllers").exists();
-    let cgroup_v1 = Path::new("/sys/fs/cgroup/cpu").exists() ||
-                    Path::new("/sys/fs/cgroup/memory").exists();
+fn read_cpuacct_usec(cgroup_path: &str, file: &str) -> Option<u64> {
+    read_trimmed(&cgroupfs::mounts().v1_file("cpuacct", cgroup_path, file)?)
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|usage_ns| usage_ns / 1000)
+}
 
-    if cgroup_v2 {
-        println!("  CGroup Version: v2 (unified hierarchy)");
-    } else if cgroup_v1 {
-        println!("  CGroup Version: v1");
-    } else {
-        println!("  CGroup Version: Not detected or not in container");
+fn get_cgroup_memory_breakdown_for_path(cgroup_path: &str) -> Option<MemoryUsageBreakdown> {
+    // Try cgroup v2 with path
+    let mounts = cgroupfs::mounts();
+    if let Some(v2_path) = mounts.v2_file(cgroup_path, "memory.stat")
+        && let Ok(contents) = probelog::read_to_string(&v2_path)
+    {
+        let stat = parse_flat_keyed(&contents);
+        let kernel_stack = stat.get("kernel_stack").copied();
+        let slab = stat.get("slab").copied();
+        // "kernel" only exists since 5.18; approximate it on older kernels
+        let kernel = stat.get("kernel").copied().or_else(|| match (kernel_stack, slab) {
+            (None, None) => None,
+            (stack, slab) => Some(stack.unwrap_or(0) + slab.unwrap_or(0) + stat.get("pagetables").copied().unwrap_or(0)),
+        });
+        return Some(MemoryUsageBreakdown {
+            anon: stat.get("anon").copied(),
+            file: stat.get("file").copied(),
+            kernel,
+            kernel_stack,
+            slab,
+            slab_reclaimable: stat.get("slab_reclaimable").copied(),
+            slab_unreclaimable: stat.get("slab_unreclaimable").copied(),
+            sock: stat.get("sock").copied(),
+            shmem: stat.get("shmem").copied(),
+            swap: read_cgroup_v2_bytes(cgroup_path, "memory.swap.current"),
+        });
     }
 
-    if let Ok(contents) = fs::read_to_string("/proc/self/cgroup") {
-        println!("  Current Process CGroups:");
-        for line in contents.lines() {
-            if !line.is_empty() {
-                println!("    {}", line);
-            }
-        }
+    // Try cgroup v1 with path
+    if let Some(v1_path) = mounts.v1_file("memory", cgroup_path, "memory.stat")
+        && let Ok(contents) = probelog::read_to_string(&v1_path)
+    {
+        let stat = parse_flat_keyed(&contents);
+        let kernel = read_cgroup_v1_file("memory", cgroup_path, "memory.kmem.usage_in_bytes")
+            .and_then(|value| value.parse::<u64>().ok());
+        return Some(MemoryUsageBreakdown {
+            anon: stat.get("rss").copied(),
+            file: stat.get("cache").copied(),
+            kernel,
+            kernel_stack: None,
+            slab: None,
+            slab_reclaimable: None,
+            slab_unreclaimable: None,
+            sock: None,
+            shmem: stat.get("shmem").copied(),
+            swap: stat.get("swap").copied(),
+        });
     }
 
-    // Show resource constraints for the current cgroup
-    let cgroup_path = get_current_cgroup_path();
-    if !cgroup_path.is_empty() && cgroup_path != "/" {
-        println!("\n  Resource Constraints for Current CGroup:");
+    None
+}
 
-        // CPU constraints
-        if let Some(cpu_quota) = get_cgroup_cpu_quota_for_path(&cgroup_path) {
-            println!("    CPU Quota: {:.2} CPUs", cpu_quota);
-        }
+/// Reads `memory.events` or `memory.events.local` (v2). For `memory.events`
+/// on v1, falls back to the `oom_kill` count in `memory.oom_control`.
+fn get_cgroup_memory_events_for_path(cgroup_path: &str, file: &str) -> Option<MemoryEvents> {
+    let mounts = cgroupfs::mounts();
+    if let Some(path) = mounts.v2_file(cgroup_path, file)
+        && let Ok(contents) = probelog::read_to_string(&path)
+    {
+        let events = parse_flat_keyed(&contents);
+        return Some(MemoryEvents {
+            low: events.get("low").copied(),
+            high: events.get("high").copied(),
+            max: events.get("max").copied(),
+            oom: events.get("oom").copied(),
+            oom_kill: events.get("oom_kill").copied(),
+        });
+    }
 
-        // Memory constraints
-        if let Some(mem_limit) = get_cgroup_memory_limit_for_path(&cgroup_path) {
-            println!("    Memory Limit: {}", humanize_bytes_binary!(mem_limit));
-        }
+    if file != "memory.events" {
+        return None;
+    }
+    let contents = probelog::read_to_string(mounts.v1_file("memory", cgroup_path, "memory.oom_control")?).ok()?;
+    let oom_kill = parse_flat_keyed(&contents).get("oom_kill").copied()?;
+    Some(MemoryEvents {
+        low: None,
+        high: None,
+        max: None,
+        oom: None,
+        oom_kill: Some(oom_kill),
+    })
+}
 
-        // Extra hint: detect if this looks like a default user.slice with no explicit limits
-        let looks_default_user = is_default_user_slice_path(&cgroup_path);
-        let explicit_limits = has_explicit_limits_at_path(&cgroup_path);
-        if looks_default_user && !explicit_limits {
-            println!("\n  Note: no explicit cpu/memory/cpuset limits detected at this cgroup; this looks like a default systemd user slice.");
+/// (headroom bytes, headroom percent): limit minus usage when both are known,
+/// otherwise system available memory as a share of system total.
+fn memory_headroom(limit: Option<u64>, usage: Option<u64>, system_total: u64, system_available: u64) -> Option<(u64, f64)> {
+    match (limit, usage) {
+        (Some(limit), Some(usage)) if limit > 0 => {
+            let headroom = limit.saturating_sub(usage);
+            Some((headroom, headroom as f64 / limit as f64 * 100.0))
         }
+        (Some(_), _) => None,
+        (None, _) if system_total > 0 => {
+            Some((system_available, system_available as f64 / system_total as f64 * 100.0))
+        }
+        (None, _) => None,
     }
 }
 
-fn is_default_user_slice_path(cgroup_path: &str) -> bool {
-    // Heuristic for systemd user sessions, e.g.: /user.slice/user-1000.slice/session-4.scope
-    cgroup_path.starts_with("/user.slice/user-") && cgroup_path.contains("/session-")
+/// Usage as a percentage of the limit, clamped to 0-100 and rounded to one
+/// decimal; None without both values.
+fn usage_percent_of_limit(usage: Option<u64>, limit: Option<u64>) -> Option<f64> {
+    let limit = limit.filter(|&limit| limit > 0)?;
+    let percent = (usage? as f64 / limit as f64 * 100.0).clamp(0.0, 100.0);
+    Some((percent * 10.0).round() / 10.0)
 }
 
-fn read_trimmed(path: &str) -> Option<String> {
-    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+/// How much this cgroup can still allocate: the room left under its limit,
+/// capped by what the host actually has free. MemAvailable alone when no
+/// limit applies.
+fn effective_available_memory(limit: Option<u64>, usage: Option<u64>, system_available: u64) -> u64 {
+    match limit {
+        Some(limit) => limit.saturating_sub(usage.unwrap_or(0)).min(system_available),
+        None => system_available,
+    }
 }
 
-fn has_explicit_limits_at_path(cgroup_path: &str) -> bool {
-    // Check cgroup v2 first
-    let v2 = Path::new("/sys/fs/cgroup/cgroup.controllers").exists();
-    if v2 {
-        // cpu.max at path set?
-        let cpu_max_path = format!("/sys/fs/cgroup{}/cpu.max", cgroup_path);
-        if let Some(line) = read_trimmed(&cpu_max_path) {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() == 2 && parts[0] != "max" {
-                return true;
-            }
-        }
-        // memory.max at path set?
-        let mem_max_path = format!("/sys/fs/cgroup{}/memory.max", cgroup_path);
-        if let Some(val) = read_trimmed(&mem_max_path) {
-            if val != "max" {
-                return true;
-            }
-        }
-        // cpuset constrained?
-        let path_cpuset = read_trimmed(&format!("/sys/fs/cgroup{}/cpuset.cpus.effective", cgroup_path));
-        let root_cpuset = read_trimmed("/sys/fs/cgroup/cpuset.cpus.effective");
-        if let (Some(p), Some(r)) = (path_cpuset, root_cpuset) {
-            if !p.is_empty() && !r.is_empty() && p != r {
-                return true;
-            }
-        }
+/// Usage minus reclaimable file cache: what the cgroup actually needs to keep.
+fn effective_memory_usage(usage: Option<u64>, breakdown: Option<&MemoryUsageBreakdown>) -> Option<u64> {
+    let usage = usage?;
+    let file = breakdown.and_then(|b| b.file).unwrap_or(0);
+    Some(usage.saturating_sub(file))
+}
+
+/// The usage that counts toward an OOM: unreclaimable memory when memory.stat
+/// breaks it down, else usage minus file cache.
+fn pressure_memory_usage(usage: Option<u64>, breakdown: Option<&MemoryUsageBreakdown>) -> Option<u64> {
+    breakdown
+        .and_then(MemoryUsageBreakdown::unreclaimable)
+        .or_else(|| effective_memory_usage(usage, breakdown))
+}
+
+/// Gap, in percentage points of the limit, between naive and reclaim-adjusted
+/// usage above which verbose output explains the difference.
+const RECLAIM_NOTE_POINTS: f64 = 10.0;
+
+/// `full avg10` memory pressure (percent) above which verbose output warns.
+const MEMORY_PSI_FULL_WARN: f64 = 1.0;
+
+/// Whether the cgroup's non-reclaimable usage has crossed `--mem-warn-threshold`
+/// for its hard limit. Page cache is left out: the kernel reclaims it before
+/// the OOM killer fires.
+fn memory_pressure_warning(threshold: MemWarnThreshold, cgroup_path: &str, usage: Option<u64>, limit: Option<u64>) -> bool {
+    let (Some(usage), Some(limit)) = (usage, limit) else {
         return false;
+    };
+    let breakdown = get_cgroup_memory_breakdown_for_path(cgroup_path);
+    let effective = pressure_memory_usage(Some(usage), breakdown.as_ref()).unwrap_or(usage);
+    threshold.exceeded(effective, limit)
+}
+
+/// Delay between consecutive `memory.current` reads when `--stable-reads` > 1.
+const STABLE_READ_INTERVAL: std::time::Duration = std::time::Duration::from_millis(20);
+
+/// Reads cgroup memory usage `reads` times and returns the median, smoothing
+/// out the rapid jitter of `memory.current`. With `reads == 1` this is a
+/// single plain read.
+fn get_cgroup_memory_usage_stable(cgroup_path: &str, reads: u32) -> Option<u64> {
+    if reads <= 1 {
+        return get_cgroup_memory_usage_for_path(cgroup_path);
     }
-    // cgroup v1: check cpu quota and memory limit at path, cpuset
-    let quota_path = format!("/sys/fs/cgroup/cpu{}/cpu.cfs_quota_us", cgroup_path);
-    let period_path = format!("/sys/fs/cgroup/cpu{}/cpu.cfs_period_us", cgroup_path);
-    if let (Some(qs), Some(ps)) = (read_trimmed(&quota_path), read_trimmed(&period_path)) {
-        if let (Ok(q), Ok(p)) = (qs.parse::<i64>(), ps.parse::<i64>()) {
-            if q > 0 && p > 0 { return true; }
+
+    let mut samples = Vec::with_capacity(reads as usize);
+    for i in 0..reads {
+        if i > 0 {
+            std::thread::sleep(STABLE_READ_INTERVAL);
         }
-    }
-    let mem_limit_path = format!("/sys/fs/cgroup/memory{}/memory.limit_in_bytes", cgroup_path);
-    if let Some(ls) = read_trimmed(&mem_limit_path) {
-        if let Ok(limit) = ls.parse::<u64>() {
-            if limit < 9223372036854771712 { return true; }
+        if let Some(usage) = get_cgroup_memory_usage_for_path(cgroup_path) {
+            samples.push(usage);
         }
     }
-    let path_cpuset = read_trimmed(&format!("/sys/fs/cgroup/cpuset{}/cpuset.cpus", cgroup_path));
-    let root_cpuset = read_trimmed("/sys/fs/cgroup/cpuset/cpuset.cpus");
-    if let (Some(p), Some(r)) = (path_cpuset, root_cpuset) {
-        if !p.is_empty() && !r.is_empty() && p != r { return true; }
+    if samples.is_empty() {
+        return None;
     }
-    false
+    samples.sort_unstable();
+    Some(samples[samples.len() / 2])
 }
 
-fn get_system_memory_from_proc() -> (u64, u64) {
-    let mut total_kb = 0u64;
-    let mut available_kb = 0u64;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::fixture_dir;
 
-    if let Ok(contents) = fs::read_to_string("/proc/meminfo") {
-        for line in contents.lines() {
-            if line.starts_with("MemTotal:") {
-                if let Some(value) = parse_meminfo_line(line) {
-                    total_kb = value;
-                }
-            } else if line.starts_with("MemAvailable:") {
-                if let Some(value) = parse_meminfo_line(line) {
-                    available_kb = value;
-                }
-            }
-        }
+    #[test]
+    fn mostly_cache_usage_is_not_pressure() {
+        const MIB: u64 = 1 << 20;
+        let breakdown = MemoryUsageBreakdown {
+            anon: Some(80 * MIB),
+            file: Some(800 * MIB),
+            kernel: Some(70 * MIB),
+            kernel_stack: Some(4 * MIB),
+            slab: Some(60 * MIB),
+            slab_reclaimable: Some(50 * MIB),
+            slab_unreclaimable: Some(10 * MIB),
+            sock: None,
+            shmem: None,
+            swap: None,
+        };
+        let usage = Some(950 * MIB);
+        assert_eq!(breakdown.reclaimable(), Some(850 * MIB));
+        assert_eq!(pressure_memory_usage(usage, Some(&breakdown)), Some(94 * MIB));
+        assert_eq!(usage_percent_of_limit(usage, Some(1000 * MIB)), Some(95.0));
+        assert_eq!(usage_percent_of_limit(Some(94 * MIB), Some(1000 * MIB)), Some(9.4));
+
+        // Without an anon figure, fall back to usage minus file cache
+        let v1 = MemoryUsageBreakdown { anon: None, slab_reclaimable: None, slab_unreclaimable: None, kernel_stack: None, ..breakdown };
+        assert_eq!(pressure_memory_usage(usage, Some(&v1)), Some(150 * MIB));
+        assert_eq!(usage_percent_of_limit(usage, None), None);
     }
 
-    // Convert from KB to bytes
-    (total_kb * 1024, available_kb * 1024)
-}
+    #[test]
+    fn limits_follow_discovered_cgroup_mounts() {
+        // v1 controllers under a chroot-style /cgroup prefix
+        let root = fixture_dir(
+            "cgroup-prefix",
+            &[
+                ("cgroup/cpu/job/cpu.cfs_quota_us", "150000\n"),
+                ("cgroup/cpu/job/cpu.cfs_period_us", "100000\n"),
+                ("cgroup/memory/job/memory.limit_in_bytes", "1073741824\n"),
+                ("cgroup/memory/idle/memory.limit_in_bytes", "9223372036854771712\n"),
+            ],
+        );
+        let prefix = root.join("cgroup").display().to_string();
+        let mounts = cgroupfs::CgroupMounts::from_mounts(&mountinfo::parse_mountinfo(&format!(
+            "40 22 0:30 / {prefix}/cpu rw - cgroup cgroup rw,cpu\n41 22 0:31 / {prefix}/memory rw - cgroup cgroup rw,memory\n"
+        )));
+        assert_eq!(get_cgroup_cpu_quota_in(&mounts, "/job"), Some(1.5));
+        assert_eq!(get_cgroup_memory_limit_in(&mounts, "/job"), Some(1 << 30));
+        assert!(has_explicit_limits_in(&mounts, "/job"));
+        assert_eq!(get_cgroup_memory_limit_in(&mounts, "/idle"), None);
+        assert!(!has_explicit_limits_in(&mounts, "/idle"));
 
-fn parse_meminfo_line(line: &str) -> Option<u64> {
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    if parts.len() >= 2 {
-        parts[1].parse::<u64>().ok()
-    } else {
-        None
+        // The same files are invisible when only cgroup2 is mounted elsewhere
+        let v2_only = cgroupfs::CgroupMounts::from_mounts(&mountinfo::parse_mountinfo(&format!(
+            "30 25 0:26 / {} rw - cgroup2 cgroup2 rw\n",
+            root.join("unified").display()
+        )));
+        assert_eq!(get_cgroup_memory_limit_in(&v2_only, "/job"), None);
+        fs::remove_dir_all(&root).unwrap();
     }
-}
 
-fn get_system_cpu_count() -> usize {
-    // Try to get the actual system CPU count by reading /proc/cpuinfo
-    if let Ok(contents) = fs::read_to_string("/proc/cpuinfo") {
-        let count = contents
-            .lines()
-            .filter(|line| line.starts_with("processor"))
-            .count();
-        if count > 0 {
-            return count;
-        }
-    }
+    #[test]
+    fn v1_cpu_and_cpuset_use_their_own_controller_paths() {
+        // cgcreate-style setup: the process sits at a different path in each hierarchy
+        let root = fixture_dir(
+            "v1-split-paths",
+            &[
+                ("cpu,cpuacct/cpu-grp/cpu.cfs_quota_us", "200000\n"),
+                ("cpu,cpuacct/cpu-grp/cpu.cfs_period_us", "100000\n"),
+                ("cpu,cpuacct/mem-grp/cpu.cfs_quota_us", "-1\n"),
+                ("cpu,cpuacct/mem-grp/cpu.cfs_period_us", "100000\n"),
+                ("memory/mem-grp/memory.limit_in_bytes", "9223372036854771712\n"),
+                ("cpuset/cpuset.cpus", "0-3\n"),
+                ("cpuset/set-grp/cpuset.cpus", "0-1\n"),
+            ],
+        );
+        let dir = |name: &str| root.join(name).display().to_string();
+        let mountinfo = format!(
+            "26 24 0:22 / {} rw - cgroup cgroup rw,cpu,cpuacct\n27 24 0:23 / {} rw - cgroup cgroup rw,memory\n28 24 0:24 / {} rw - cgroup cgroup rw,cpuset\n",
+            dir("cpu,cpuacct"),
+            dir("memory"),
+            dir("cpuset")
+        );
+        let mounts = cgroupfs::CgroupMounts::from_mounts(&mountinfo::parse_mountinfo(&mountinfo))
+            .with_process_cgroups("6:cpuset:/set-grp\n5:cpu,cpuacct:/cpu-grp\n4:memory:/mem-grp\n");
+        assert_eq!(mounts.process_path.as_deref(), Some("/mem-grp"));
 
-    // Fallback to sysconf if available
-    unsafe {
-        let count = libc::sysconf(libc::_SC_NPROCESSORS_ONLN);
-        if count > 0 {
-            return count as usize;
-        }
+        // `--cgroup-path /mem-grp` is not our own cgroup and is used as given
+        assert_eq!(mounts.v1_file("cpuset", "/mem-grp", "cpuset.cpus"), Some(format!("{}/mem-grp/cpuset.cpus", dir("cpuset"))));
+        assert_eq!(get_cgroup_cpu_quota_in(&mounts, "/mem-grp"), None);
+
+        mounts.set_own_cgroup("/mem-grp");
+
+        // Our own cgroup reads the cpu hierarchy at /cpu-grp, not the memory path
+        assert_eq!(get_cgroup_cpu_quota_in(&mounts, "/mem-grp"), Some(2.0));
+        assert_eq!(gather::read_cpu_quota_in(&mounts, "/mem-grp").unwrap(), Some(2.0));
+        assert_eq!(mounts.v1_file("cpuset", "/mem-grp", "cpuset.cpus"), Some(format!("{}/set-grp/cpuset.cpus", dir("cpuset"))));
+
+        // Only the cpuset differs from the root once the cpu quota is lifted
+        let mut without_cpu = mounts.clone();
+        without_cpu.controllers.remove("cpu");
+        assert!(has_explicit_limits_in(&without_cpu, "/mem-grp"));
+
+        // An explicitly requested path is used as given in every hierarchy
+        assert_eq!(get_cgroup_cpu_quota_in(&mounts, "/cpu-grp"), Some(2.0));
+        assert_eq!(gather::read_cpu_quota_in(&mounts, "/other").unwrap(), None);
+        fs::remove_dir_all(&root).unwrap();
     }
 
-    // Last resort: use num_cpus (which may be cgroup limited)
-    num_cpus::get()
-}
+    #[test]
+    fn host_cgroup_paths_resolve_inside_container() {
+        // Container without a cgroup namespace: /sys/fs/cgroup is the
+        // container's cgroup, but /proc/self/cgroup shows the host path
+        let present = ["/", "/app"];
+        let exists = |path: &str| present.contains(&path);
+        let resolve = |path: &str, root: Option<&str>| resolve_cgroup_path(path, root, exists);
+        assert_eq!(resolve("/app", None), ("/app".to_string(), "direct"));
+        assert_eq!(resolve("/docker/abc/app", Some("/docker/abc")), ("/app".to_string(), "suffix"));
+        assert_eq!(resolve("/docker/abc", Some("/docker/abc")), ("/".to_string(), "mount-root"));
+        // "/docker/abcdef" is not under "/docker/abc"
+        assert_eq!(resolve("/docker/abcdef", Some("/docker/abc")), ("/docker/abcdef".to_string(), "unresolved"));
 
-fn get_system_physical_cpu_count() -> usize {
-    // Try to get physical cores by parsing /proc/cpuinfo
-    if let Ok(contents) = fs::read_to_string("/proc/cpuinfo") {
-        let mut core_ids = HashSet::new();
-        let mut current_physical_id = None;
+        let mounts = mountinfo::parse_mountinfo(
+            "30 25 0:26 /docker/abc /sys/fs/cgroup ro,nosuid - cgroup2 cgroup2 rw,nsdelegate\n",
+        );
+        assert_eq!(cgroup_mount_root(&mounts).as_deref(), Some("/docker/abc"));
+    }
 
-        for line in contents.lines() {
-            if line.starts_with("physical id") {
-                current_physical_id = line.split(':')
-                    .nth(1)
-                    .and_then(|s| s.trim().parse::<usize>().ok());
-            } else if line.starts_with("core id") {
-                if let Some(phys_id) = current_physical_id {
-                    if let Some(core_id) = line.split(':')
-                        .nth(1)
-                        .and_then(|s| s.trim().parse::<usize>().ok()) {
-                        core_ids.insert((phys_id, core_id));
-                    }
-                }
-            }
-        }
+    #[test]
+    fn v1_explicit_unlimited_quota_skips_root_fallback() {
+        let root = fixture_dir(
+            "v1-quota",
+            &[
+                ("cpu.cfs_quota_us", "200000\n"),
+                ("cpu.cfs_period_us", "100000\n"),
+                ("job/cpu.cfs_quota_us", "-1\n"),
+                ("job/cpu.cfs_period_us", "100000\n"),
+                ("empty/.keep", ""),
+            ],
+        );
+        assert_eq!(read_v1_cpu_quota_in(&root, "/job"), V1CpuQuota::Unlimited);
+        assert_eq!(v1_cpu_quota_with_fallback(&root, "/job", true), None);
+        assert_eq!(v1_cpu_quota_with_fallback(&root, "/job", false), None);
+        // Only an unreadable level falls back, and only when allowed
+        assert_eq!(read_v1_cpu_quota_in(&root, "/empty"), V1CpuQuota::Unreadable);
+        assert_eq!(v1_cpu_quota_with_fallback(&root, "/empty", true), Some(2.0));
+        assert_eq!(v1_cpu_quota_with_fallback(&root, "/empty", false), None);
+        fs::remove_dir_all(root).unwrap();
+    }
 
-        if !core_ids.is_empty() {
-            return core_ids.len();
-        }
+    #[test]
+    fn physical_cores_arm64_clusters_with_repeated_core_ids() {
+        // Two 4-core clusters; core_id restarts at 0 in each cluster
+        let files: Vec<(String, String)> = (0..8)
+            .flat_map(|cpu| {
+                [
+                    (format!("cpu{}/topology/core_cpus_list", cpu), format!("{}\n", cpu)),
+                    (format!("cpu{}/topology/core_id", cpu), format!("{}\n", cpu % 4)),
+                ]
+            })
+            .collect();
+        let root = fixture_dir("topology-arm64", &files);
+        assert_eq!(count_physical_cores_sysfs(&root), Some(8));
+        fs::remove_dir_all(root).unwrap();
     }
 
-    // Fallback: use num_cpus for physical cores
-    num_cpus::get_physical()
-}
+    #[test]
+    fn physical_cores_x86_smt_siblings_count_once() {
+        let root = fixture_dir(
+            "topology-smt",
+            &[
+                ("cpu0/topology/core_cpus_list", "0,2\n"),
+                ("cpu1/topology/core_cpus_list", "1,3\n"),
+                ("cpu2/topology/core_cpus_list", "0,2\n"),
+                ("cpu3/topology/core_cpus_list", "1,3\n"),
+                ("cpufreq/boost", "1\n"),
+            ],
+        );
+        assert_eq!(count_physical_cores_sysfs(&root), Some(2));
+        fs::remove_dir_all(root).unwrap();
+    }
 
-fn get_current_cgroup_path() -> String {
-    if let Ok(contents) = fs::read_to_string("/proc/self/cgroup") {
-        // For cgroup v2, the format is: 0::/path
-        for line in contents.lines() {
-            if line.starts_with("0::") {
-                return line[3..].to_string();
-            }
-        }
+    #[test]
+    fn physical_cores_package_and_core_id_without_sibling_lists() {
+        let root = fixture_dir(
+            "topology-ids",
+            &[
+                ("cpu0/topology/physical_package_id", "0\n"),
+                ("cpu0/topology/core_id", "0\n"),
+                ("cpu1/topology/physical_package_id", "1\n"),
+                ("cpu1/topology/core_id", "0\n"),
+            ],
+        );
+        assert_eq!(count_physical_cores_sysfs(&root), Some(2));
+        fs::remove_dir_all(root).unwrap();
+    }
 
-        // For cgroup v1, get the memory controller path
-        for line in contents.lines() {
-            if line.contains(":memory:") {
-                let parts: Vec<&str> = line.split(':').collect();
-                if parts.len() >= 3 {
-                    return parts[2].to_string();
-                }
-            }
-        }
+    #[test]
+    fn physical_cores_vm_without_topology_falls_back() {
+        let root = fixture_dir("topology-vm", &[("cpu0/online", "1\n"), ("online", "0\n")]);
+        assert_eq!(count_physical_cores_sysfs(&root), None);
+        fs::remove_dir_all(root).unwrap();
     }
-    String::new()
-}
 
-fn get_cgroup_cpu_quota() -> Option<f64> {
-    let cgroup_path = get_current_cgroup_path();
-    get_cgroup_cpu_quota_for_path(&cgroup_path)
-}
+    #[test]
+    fn byte_size_accepts_suffixes() {
+        assert_eq!(parse_byte_size("4096"), Ok(4096));
+        assert_eq!(parse_byte_size("512M"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_byte_size("8G"), Ok(8 * 1024 * 1024 * 1024));
+        assert_eq!(parse_byte_size("1.5GiB"), Ok(1536 * 1024 * 1024));
+        assert_eq!(parse_byte_size("2k"), Ok(2048));
+        assert!(parse_byte_size("8X").is_err());
+        assert!(parse_byte_size("G").is_err());
+    }
 
-fn get_cgroup_cpu_quota_for_path(cgroup_path: &str) -> Option<f64> {
-    // Try cgroup v2 first
-    if let Ok(quota) = read_cgroup_v2_cpu_quota_for_path(cgroup_path) {
-        return Some(quota);
+    #[test]
+    fn effective_available_is_the_tighter_of_limit_and_host() {
+        assert_eq!(effective_available_memory(Some(8 << 30), Some(6 << 30), 20 << 30), 2 << 30);
+        assert_eq!(effective_available_memory(Some(8 << 30), Some(1 << 30), 3 << 30), 3 << 30);
+        assert_eq!(effective_available_memory(Some(1 << 30), Some(2 << 30), 3 << 30), 0);
+        assert_eq!(effective_available_memory(None, Some(1 << 30), 3 << 30), 3 << 30);
     }
 
-    // Fall back to cgroup v1
-    read_cgroup_v1_cpu_quota_for_path(cgroup_path)
-}
+    #[test]
+    fn zswap_max_is_unlimited() {
+        let dir = fixture_dir("zswap", &[
+            ("memory.zswap.max", "max\n"),
+            ("memory.zswap.current", "1048576\n"),
+            ("memory.zswap.writeback", "0\n"),
+        ]);
+        assert_eq!(
+            read_cgroup_zswap_in(&dir),
+            Some(ZswapInfo { limit_bytes: None, current_bytes: Some(1 << 20), writeback_enabled: Some(false) })
+        );
+        fs::write(dir.join("memory.zswap.max"), "536870912\n").unwrap();
+        fs::remove_file(dir.join("memory.zswap.writeback")).unwrap();
+        let zswap = read_cgroup_zswap_in(&dir).unwrap();
+        assert_eq!((zswap.limit_bytes, zswap.writeback_enabled), (Some(512 << 20), None));
+        assert_eq!(read_cgroup_zswap_in(&dir.join("missing")), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
-fn read_cgroup_v2_cpu_quota_for_path(cgroup_path: &str) -> Result<f64, Box<dyn std::error::Error>> {
-    let cpu_max_path = format!("/sys/fs/cgroup{}/cpu.max", cgroup_path);
+    #[test]
+    fn system_memory_falls_back_to_sysinfo_without_meminfo() {
+        let (total, available) = system_memory_from(Path::new("/nonexistent/systemcheck/meminfo"));
+        assert!(total > 0);
+        assert!(available > 0 && available <= total);
+    }
 
-    // Try the specific cgroup path first
-    if let Ok(cpu_max) = fs::read_to_string(&cpu_max_path) {
-        let parts: Vec<&str> = cpu_max.trim().split_whitespace().collect();
-        if parts.len() == 2 && parts[0] != "max" {
-            let quota: i64 = parts[0].parse()?;
-            let period: i64 = parts[1].parse()?;
-            return Ok(quota as f64 / period as f64);
-        }
+    #[test]
+    fn cpu_burst_counters_are_optional() {
+        let v2 = parse_cpu_throttling("usage_usec 10\nnr_periods 100\nnr_throttled 7\nthrottled_usec 5000\nnr_bursts 3\nburst_usec 1200\n", false).unwrap();
+        assert_eq!((v2.nr_throttled, v2.throttled_usec, v2.nr_bursts, v2.burst_usec), (7, 5000, Some(3), Some(1200)));
+        let v1 = parse_cpu_throttling("nr_periods 100\nnr_throttled 7\nthrottled_time 5000000\nnr_bursts 3\nburst_time 1200000\n", true).unwrap();
+        assert_eq!((v1.throttled_usec, v1.burst_usec), (5000, Some(1200)));
+        // Kernels before 5.14 have no burst counters
+        let old = parse_cpu_throttling("nr_periods 100\nnr_throttled 7\nthrottled_usec 5000\n", false).unwrap();
+        assert_eq!((old.nr_bursts, old.burst_usec), (None, None));
+        assert!(parse_cpu_throttling("usage_usec 10\n", false).is_none());
     }
 
-    // Fall back to root cgroup
-    let cpu_max = fs::read_to_string("/sys/fs/cgroup/cpu.max")?;
-    let parts: Vec<&str> = cpu_max.trim().split_whitespace().collect();
+    #[test]
+    fn mem_warn_threshold_is_a_percentage_or_a_headroom() {
+        assert_eq!(parse_mem_warn_threshold("90"), Ok(MemWarnThreshold::Percent(90.0)));
+        assert_eq!(parse_mem_warn_threshold("75%"), Ok(MemWarnThreshold::Percent(75.0)));
+        assert_eq!(parse_mem_warn_threshold("500MiB"), Ok(MemWarnThreshold::Headroom(500 << 20)));
+        assert!(parse_mem_warn_threshold("150").is_err());
+        assert!(parse_mem_warn_threshold("lots").is_err());
 
-    if parts.len() == 2 && parts[0] != "max" {
-        let quota: i64 = parts[0].parse()?;
-        let period: i64 = parts[1].parse()?;
-        return Ok(quota as f64 / period as f64);
+        let limit = 4 << 30;
+        assert!(MemWarnThreshold::Percent(90.0).exceeded(3900 << 20, limit));
+        assert!(!MemWarnThreshold::Percent(90.0).exceeded(2 << 30, limit));
+        assert!(MemWarnThreshold::Headroom(500 << 20).exceeded(3700 << 20, limit));
+        assert!(!MemWarnThreshold::Headroom(500 << 20).exceeded(3 << 30, limit));
     }
 
-    Err("No CPU quota set in cgroup v2".into())
-}
+    #[test]
+    fn usage_percent_is_clamped_and_rounded() {
+        assert_eq!(usage_percent_of_limit(Some(1 << 30), Some(3 << 30)), Some(33.3));
+        assert_eq!(usage_percent_of_limit(Some(5 << 30), Some(4 << 30)), Some(100.0));
+        assert_eq!(usage_percent_of_limit(Some(1 << 30), None), None);
+        assert_eq!(usage_percent_of_limit(None, Some(4 << 30)), None);
+    }
 
-fn read_cgroup_v1_cpu_quota() -> Option<f64> {
-    let quota_path = "/sys/fs/cgroup/cpu/cpu.cfs_quota_us";
-    let period_path = "/sys/fs/cgroup/cpu/cpu.cfs_period_us";
+    #[test]
+    fn memory_high_alone_is_soft_enforcement() {
+        let total = 16 << 30;
+        assert_eq!(Constraints::new(None, Some(512 << 20), Some(256 << 20), total).memory_enforcement(), "hard");
+        let soft = Constraints::new(None, None, Some(512 << 20), total);
+        assert_eq!(soft.memory_enforcement(), "soft");
+        assert_eq!(
+            soft.describe_memory_enforcement(Units::Binary).as_deref(),
+            Some("soft limit (throttle at 512 MiB, no hard cap)")
+        );
+        assert_eq!(Constraints::new(None, None, None, total).memory_enforcement(), "none");
+    }
 
-    if let (Ok(quota_str), Ok(period_str)) = (
-        fs::read_to_string(quota_path),
-        fs::read_to_string(period_path)
-    ) {
-        if let (Ok(quota), Ok(period)) = (
-            quota_str.trim().parse::<i64>(),
-            period_str.trim().parse::<i64>()
-        ) {
-            if quota > 0 && period > 0 {
-                return Some(quota as f64 / period as f64);
-            }
+    #[test]
+    fn rlimit_warning_needs_a_larger_cgroup_limit() {
+        let mut limits = BTreeMap::new();
+        limits.insert("as".to_string(), rlimits::Rlimit { soft: Some(1 << 30), hard: None });
+        limits.insert("nofile".to_string(), rlimits::Rlimit { soft: Some(1024), hard: Some(4096) });
+        assert!(rlimit_warnings(&limits, None, Units::Binary).is_empty());
+        assert!(rlimit_warnings(&limits, Some(1 << 29), Units::Binary).is_empty());
+        let warnings = rlimit_warnings(&limits, Some(4 << 30), Units::Binary);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("RLIMIT_AS is 1 GiB"));
+        assert!(rlimit_warnings(&limits, Some(4 << 30), Units::Bytes)[0].starts_with("RLIMIT_AS is 1073741824 B"));
+    }
+
+    #[test]
+    fn interval_rejects_values_that_would_panic_or_spin() {
+        assert_eq!(parse_interval("0.5"), Ok(0.5));
+        for value in ["-1", "nan", "inf", "0", "0.01", "soon"] {
+            assert!(parse_interval(value).is_err(), "{}", value);
         }
     }
 
-    None
-}
+    #[test]
+    fn check_config_reports_every_mismatch() {
+        let root = fixture_dir("check-config", &[("limits.toml", "cpus = 2\nmemory = \"8G\"\npids = \"max\"\n")]);
+        let config = parse_check_config(&root.join("limits.toml").display().to_string()).unwrap();
+        assert_eq!(config.memory, Some(manifest::Expected::Limit(8 << 30)));
 
-fn read_cgroup_v1_cpu_quota_for_path(cgroup_path: &str) -> Option<f64> {
-    let quota_path = format!("/sys/fs/cgroup/cpu{}/cpu.cfs_quota_us", cgroup_path);
-    let period_path = format!("/sys/fs/cgroup/cpu{}/cpu.cfs_period_us", cgroup_path);
+        assert!(check_config_mismatches(&config, Some(2.1), Some(8 << 30), None, 0.15, Units::Binary).is_empty());
+        let mismatches = check_config_mismatches(&config, None, Some(4 << 30), Some(512), 0.15, Units::Binary);
+        let keys: Vec<&str> = mismatches.iter().map(|(key, _, _)| *key).collect();
+        assert_eq!(keys, ["cpus", "memory", "pids"]);
+        assert_eq!(mismatches[0].2, "unlimited");
+        assert_eq!(mismatches[2], ("pids", "unlimited".to_string(), "512".to_string()));
 
-    if let (Ok(quota_str), Ok(period_str)) = (
-        fs::read_to_string(&quota_path),
-        fs::read_to_string(&period_path)
-    ) {
-        if let (Ok(quota), Ok(period)) = (
-            quota_str.trim().parse::<i64>(),
-            period_str.trim().parse::<i64>()
-        ) {
-            if quota > 0 && period > 0 {
-                return Some(quota as f64 / period as f64);
-            }
-        }
+        fs::write(root.join("typo.json"), r#"{"cpu": 2}"#).unwrap();
+        assert!(parse_check_config(&root.join("typo.json").display().to_string()).unwrap_err().contains("unknown key 'cpu'"));
+        fs::remove_dir_all(&root).unwrap();
     }
 
-    // Fall back to root cgroup
-    read_cgroup_v1_cpu_quota()
-}
+    #[test]
+    fn assert_cpus_allows_tolerance() {
+        assert_eq!(assert_cpus_failure(2.0, 0.15, Some(2.1)), None);
+        assert_eq!(
+            assert_cpus_failure(2.0, 0.15, Some(1.5)).as_deref(),
+            Some("expected 2.00 CPUs (±0.15), found a quota of 1.50 CPUs")
+        );
+        assert!(assert_cpus_failure(2.0, 0.15, None).is_some());
+    }
 
-fn get_cgroup_memory_limit_for_path(cgroup_path: &str) -> Option<u64> {
-    // Try cgroup v2
-    let mem_max_path = format!("/sys/fs/cgroup{}/memory.max", cgroup_path);
-    if let Ok(limit_str) = fs::read_to_string(&mem_max_path) {
-        if let Ok(limit) = limit_str.trim().parse::<u64>() {
-            if limit < u64::MAX {
-                return Some(limit);
+    /// Checks `value` against the subset of JSON Schema that schemars emits.
+    fn schema_violations(value: &serde_json::Value, schema: &serde_json::Value, root: &serde_json::Value, at: &str) -> Vec<String> {
+        use serde_json::Value;
+
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            return schema_violations(value, &root["$defs"][name], root, at);
+        }
+        if let Some(options) = schema["anyOf"].as_array() {
+            if options.iter().any(|option| schema_violations(value, option, root, at).is_empty()) {
+                return Vec::new();
             }
+            return vec![format!("{}: matches none of anyOf", at)];
+        }
+        let types: Vec<&str> = match &schema["type"] {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let type_ok = types.is_empty()
+            || types.iter().any(|&name| match name {
+                "null" => value.is_null(),
+                "boolean" => value.is_boolean(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "number" => value.is_number(),
+                "string" => value.is_string(),
+                "array" => value.is_array(),
+                "object" => value.is_object(),
+                _ => false,
+            });
+        if !type_ok {
+            return vec![format!("{}: {} is not {:?}", at, value, types)];
         }
-    }
 
-    // Try cgroup v2 root
-    if let Ok(limit_str) = fs::read_to_string("/sys/fs/cgroup/memory.max") {
-        if let Ok(limit) = limit_str.trim().parse::<u64>() {
-            if limit < u64::MAX {
-                return Some(limit);
+        let mut violations = Vec::new();
+        if let Value::Object(fields) = value {
+            for required in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+                if !fields.contains_key(required) {
+                    violations.push(format!("{}: missing {}", at, required));
+                }
+            }
+            for (name, field) in fields {
+                let path = format!("{}.{}", at, name);
+                match (&schema["properties"][name.as_str()], &schema["additionalProperties"]) {
+                    (Value::Null, Value::Bool(false)) => violations.push(format!("{}: not in schema", path)),
+                    (Value::Null, Value::Object(_)) => {
+                        violations.extend(schema_violations(field, &schema["additionalProperties"], root, &path))
+                    }
+                    (Value::Null, _) if schema["properties"].is_object() => {
+                        violations.push(format!("{}: not in schema", path))
+                    }
+                    (Value::Null, _) => {}
+                    (property, _) => violations.extend(schema_violations(field, property, root, &path)),
+                }
             }
         }
-    }
-
-    // Try cgroup v1 with path
-    let mem_limit_path = format!("/sys/fs/cgroup/memory{}/memory.limit_in_bytes", cgroup_path);
-    if let Ok(limit_str) = fs::read_to_string(&mem_limit_path) {
-        if let Ok(limit) = limit_str.trim().parse::<u64>() {
-            // Check if it's not the default unlimited value
-            if limit < 9223372036854771712 {
-                return Some(limit);
+        if let (Value::Array(items), Value::Object(_)) = (value, &schema["items"]) {
+            for (idx, item) in items.iter().enumerate() {
+                violations.extend(schema_violations(item, &schema["items"], root, &format!("{}[{}]", at, idx)));
             }
         }
+        violations
     }
 
-    // Try cgroup v1 root
-    if let Ok(limit_str) = fs::read_to_string("/sys/fs/cgroup/memory/memory.limit_in_bytes") {
-        if let Ok(limit) = limit_str.trim().parse::<u64>() {
-            // Check if it's not the default unlimited value
-            if limit < 9223372036854771712 {
-                return Some(limit);
-            }
-        }
+    #[test]
+    fn reports_match_the_json_schema() {
+        let schema = report_json_schema();
+        assert!(schema["title"].as_str().unwrap().contains(&SCHEMA_VERSION.to_string()));
+
+        let cli = Cli::parse_from(["systemcheck", "-v", "--steal-sample-ms", "0"]);
+        let detailed = serde_json::to_value(gather_detailed_report(&cli)).unwrap();
+        let violations = schema_violations(&detailed, &schema["anyOf"][1], &schema, "detailed");
+        assert!(violations.is_empty(), "{:#?}", violations);
+
+        let simple = serde_json::to_value(SimpleReport {
+            version: VERSION.to_string(),
+            timestamp: "2025-01-31T14:05:09Z".to_string(),
+            cpu: SimpleCpuSummary {
+                available_cpus: 4,
+                system_logical_cpus: 16,
+                constrained: true,
+                sub_core_quota: false,
+                constraint_reason: Some("cpu.max quota".to_string()),
+            },
+            memory: SimpleMemorySummary {
+                system_available_bytes: 8 << 30,
+                cgroup_memory_limit_bytes: Some(2 << 30),
+                effective_available_bytes: 1 << 30,
+                cgroup_memory_usage_bytes: Some(1 << 30),
+                usage_percent_of_limit: Some(50.0),
+                constrained: true,
+                limit_kind: Some("max".to_string()),
+                enforcement: "hard".to_string(),
+                memory_pressure_warning: false,
+            },
+        })
+        .unwrap();
+        let violations = schema_violations(&simple, &schema["anyOf"][0], &schema, "simple");
+        assert!(violations.is_empty(), "{:#?}", violations);
+
+        // The checker must actually reject things
+        let mut broken = simple.clone();
+        broken["memory"]["constrained"] = serde_json::json!("yes");
+        assert!(!schema_violations(&broken, &schema["anyOf"][0], &schema, "simple").is_empty());
     }
 
-    None
-}
+    #[test]
+    fn autogroup_nice_follows_the_group_name() {
+        assert_eq!(parse_autogroup_nice("/autogroup-327 nice 0\n"), Some(0));
+        assert_eq!(parse_autogroup_nice("/autogroup-12 nice -5\n"), Some(-5));
+        assert_eq!(parse_autogroup_nice("/autogroup-12\n"), None);
+    }
 
-fn get_cgroup_memory_usage_for_path(cgroup_path: &str) -> Option<u64> {
-    // Try cgroup v2 with path
-    let mem_current_path = format!("/sys/fs/cgroup{}/memory.current", cgroup_path);
-    if let Ok(usage_str) = fs::read_to_string(&mem_current_path) {
-        if let Ok(usage) = usage_str.trim().parse::<u64>() {
-            return Some(usage);
-        }
+    #[test]
+    fn sysfs_choice_is_the_bracketed_entry() {
+        assert_eq!(parse_sysfs_choice("always [madvise] never"), Some("madvise"));
+        assert_eq!(parse_sysfs_choice("[always] defer defer+madvise madvise never"), Some("always"));
+        assert_eq!(parse_sysfs_choice("always madvise never"), None);
     }
 
-    // Try cgroup v2 root
-    if let Ok(usage_str) = fs::read_to_string("/sys/fs/cgroup/memory.current") {
-        if let Ok(usage) = usage_str.trim().parse::<u64>() {
-            return Some(usage);
-        }
+    #[test]
+    fn simple_cpu_line_names_quota_assigned_and_system_cpus() {
+        assert_eq!(
+            simple_cpu_line(Some("cpu.max quota"), Some(1.5), 2, 16).as_deref(),
+            Some("Constrained to ~1.5 CPUs (quota) on 2 assigned CPUs of 16 system CPUs")
+        );
+        assert_eq!(
+            simple_cpu_line(Some("cpu.max quota"), Some(3.0), 4, 4).as_deref(),
+            Some("Constrained to ~3 CPUs (quota) on all 4 system CPUs")
+        );
+        assert_eq!(
+            simple_cpu_line(Some("cpuset"), Some(8.0), 2, 16).as_deref(),
+            Some("Constrained to 2 assigned CPUs of 16 system CPUs (cpuset), quota ~8 CPUs")
+        );
+        assert_eq!(simple_cpu_line(None, None, 16, 16), None);
     }
 
-    // Try cgroup v1 with path
-    let mem_usage_path = format!("/sys/fs/cgroup/memory{}/memory.usage_in_bytes", cgroup_path);
-    if let Ok(usage_str) = fs::read_to_string(&mem_usage_path) {
-        if let Ok(usage) = usage_str.trim().parse::<u64>() {
-            return Some(usage);
-        }
+    #[test]
+    fn meminfo_line_accepts_kilobyte_unit_variants() {
+        assert_eq!(parse_meminfo_line("MemTotal:       16318480 kB"), Some(("MemTotal", 16318480 * 1024)));
+        assert_eq!(parse_meminfo_line("MemTotal: 16318480 KB"), Some(("MemTotal", 16318480 * 1024)));
+        assert_eq!(parse_meminfo_line("MemTotal: 16318480 kb"), Some(("MemTotal", 16318480 * 1024)));
+        assert_eq!(parse_meminfo_line("MemTotal: 16318480"), Some(("MemTotal", 16318480 * 1024)));
     }
 
-    // Try cgroup v1 root
-    if let Ok(usage_str) = fs::read_to_string("/sys/fs/cgroup/memory/memory.usage_in_bytes") {
-        if let Ok(usage) = usage_str.trim().parse::<u64>() {
-            return Some(usage);
-        }
+    #[test]
+    fn kernel_memory_reads_slab_and_page_tables() {
+        let contents = "Slab:             900000 kB\nSReclaimable:     600000 kB\nSUnreclaim:       300000 kB\nKernelStack:       16384 kB\nPageTables:        40960 kB\nSecPageTables:         0 kB\n";
+        let kernel = parse_kernel_memory(contents);
+        assert_eq!(kernel.slab_bytes, Some(900000 * 1024));
+        assert_eq!(kernel.slab_unreclaimable_bytes, Some(300000 * 1024));
+        assert_eq!(kernel.page_tables_bytes, Some(40960 * 1024));
+        assert_eq!(parse_kernel_memory("MemTotal: 1024 kB\n"), KernelMemory::default());
     }
 
-    None
-}
\ No newline at end of file
+    #[test]
+    fn dirty_pages_warn_above_a_tenth_of_ram() {
+        let page_cache = parse_page_cache("Buffers: 1024 kB\nCached: 4096000 kB\nDirty: 2000000 kB\nWriteback: 0 kB\n");
+        assert_eq!(page_cache.cached_bytes, Some(4096000 * 1024));
+        assert_eq!(page_cache.writeback_bytes, Some(0));
+        assert!(dirty_pages_warning(&page_cache, 16 << 30, Units::Binary).is_some());
+        assert!(dirty_pages_warning(&page_cache, 64 << 30, Units::Binary).is_none());
+    }
+
+    #[test]
+    fn default_hugepage_size_comes_from_meminfo() {
+        // ARM64 with 64 KiB base pages defaults to 512 MiB huge pages
+        let root = fixture_dir("hugepagesize", &[("meminfo", "MemTotal:       65536000 kB\nHugepagesize:     524288 kB\n")]);
+        assert_eq!(read_meminfo_value_in(&root.join("meminfo"), "Hugepagesize"), Some(512 << 20));
+        assert_eq!(read_meminfo_value_in(&root.join("meminfo"), "AnonHugePages"), None);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn meminfo_line_rejects_unexpected_units_and_garbage() {
+        assert_eq!(parse_meminfo_line("MemTotal: 16318480 MB"), None);
+        assert_eq!(parse_meminfo_line("MemTotal: 16318480 B"), None);
+        assert_eq!(parse_meminfo_line("MemTotal: lots kB"), None);
+        assert_eq!(parse_meminfo_line("MemTotal"), None);
+    }
+
+    #[test]
+    fn meminfo_totals_match_keys_case_insensitively() {
+        let contents = "memtotal: 2048 kB\nMEMAVAILABLE: 1024 KB\nMemFree: 512 kB\n";
+        assert_eq!(parse_meminfo_totals(contents), (2048 * 1024, 1024 * 1024));
+    }
+
+    #[test]
+    fn mem_available_is_estimated_on_old_kernels() {
+        // /proc/meminfo from a 3.10 enterprise kernel, before MemAvailable existed
+        let contents = "MemTotal:       16268024 kB\nMemFree:         2131960 kB\nBuffers:          412356 kB\nCached:         9262816 kB\n\
+             SwapCached:        10452 kB\nActive:         7926524 kB\nInactive:       4979368 kB\nShmem:            305776 kB\n\
+             Slab:            949108 kB\nSReclaimable:     832440 kB\n";
+        assert!(!meminfo_has_available(contents));
+        assert_eq!(parse_meminfo_totals(contents), (16268024 * 1024, (2131960 + 412356 + 9262816 - 305776) * 1024));
+        // A reported MemAvailable is used as is, even when zero
+        assert!(meminfo_has_available("MemTotal: 2048 kB\nMemAvailable: 0 kB\nMemFree: 512 kB\n"));
+        assert_eq!(parse_meminfo_totals("MemTotal: 2048 kB\nMemAvailable: 0 kB\nMemFree: 512 kB\n"), (2048 * 1024, 0));
+    }
+
+    #[test]
+    fn meminfo_totals_skip_lines_with_wrong_unit() {
+        let contents = "MemTotal: 2048 kB\nMemAvailable: 1 GB\n";
+        assert_eq!(parse_meminfo_totals(contents), (2048 * 1024, 0));
+    }
+}
@@ -0,0 +1,120 @@
+/// An expected limit from a `--check-config` manifest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expected<T> {
+    Limit(T),
+    /// "max", "unlimited" or "none": no limit should be set
+    Unlimited,
+}
+
+impl<T> Expected<T> {
+    pub fn parse(value: &str, parse_limit: impl Fn(&str) -> Result<T, String>) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "max" | "unlimited" | "none" => Ok(Expected::Unlimited),
+            _ => parse_limit(value.trim()).map(Expected::Limit),
+        }
+    }
+
+    /// The limit, or None when the manifest expects none.
+    pub fn limit(self) -> Option<T> {
+        match self {
+            Expected::Limit(limit) => Some(limit),
+            Expected::Unlimited => None,
+        }
+    }
+}
+
+/// Reads a manifest as (key, value) pairs in file order. JSON when the file
+/// is an object, else a flat TOML subset: `key = value` lines, `#` comments,
+/// quoted or bare values and an optional `[limits]` table header. Values
+/// are returned as text so the caller can apply its own size and count parsers.
+pub fn parse(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let entries = if contents.trim_start().starts_with('{') { parse_json(contents)? } else { parse_toml(contents)? };
+    for (index, (key, _)) in entries.iter().enumerate() {
+        if entries[..index].iter().any(|(earlier, _)| earlier == key) {
+            return Err(format!("'{}' is set more than once", key));
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_json(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let value: serde_json::Value = serde_json::from_str(contents).map_err(|err| format!("invalid JSON: {}", err))?;
+    let Some(object) = value.as_object() else {
+        return Err("expected a JSON object".to_string());
+    };
+    object
+        .iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(text) => Ok((key.clone(), text.clone())),
+            serde_json::Value::Number(number) => Ok((key.clone(), number.to_string())),
+            serde_json::Value::Null => Ok((key.clone(), "none".to_string())),
+            _ => Err(format!("'{}' must be a number or a string", key)),
+        })
+        .collect()
+}
+
+fn parse_toml(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() || line == "[limits]" {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected `key = value`", number + 1));
+        };
+        let value = value.trim();
+        let unquoted = ['"', '\'']
+            .iter()
+            .find_map(|&quote| value.strip_prefix(quote)?.strip_suffix(quote))
+            .unwrap_or(value);
+        entries.push((key.trim().to_string(), unquoted.to_string()));
+    }
+    Ok(entries)
+}
+
+/// Drops a `#` comment unless it sits inside a quoted value.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '#') => return &line[..index],
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_and_json_manifests_agree() {
+        let toml = "\
+# expected limits for the batch nodes
+[limits]
+cpus = 2
+memory = \"8G\"   # hard limit
+pids = 'max'
+";
+        let json = r#"{"cpus": 2, "memory": "8G", "pids": "max"}"#;
+        let expected = vec![
+            ("cpus".to_string(), "2".to_string()),
+            ("memory".to_string(), "8G".to_string()),
+            ("pids".to_string(), "max".to_string()),
+        ];
+        assert_eq!(parse(toml).unwrap(), expected);
+        assert_eq!(parse(json).unwrap(), expected);
+
+        assert!(parse("cpus = 2\ncpus = 4\n").unwrap_err().contains("more than once"));
+        assert!(parse("cpus 2\n").unwrap_err().contains("line 1"));
+        assert!(parse(r#"{"cpus": [2]}"#).is_err());
+
+        let parse_count = |value: &str| value.parse::<u64>().map_err(|err| err.to_string());
+        assert_eq!(Expected::parse("Unlimited", parse_count), Ok(Expected::Unlimited));
+        assert_eq!(Expected::parse("512", parse_count), Ok(Expected::Limit(512)));
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Readings kept for the trend; older ones fall out so the rate follows
+/// recent behaviour rather than the whole run.
+const WINDOW: usize = 10;
+
+/// Timestamped `memory.current` readings from `--watch`/`--samples`, used to
+/// estimate how fast a cgroup's memory is growing.
+#[derive(Debug, Default)]
+pub struct MemoryTrend {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl MemoryTrend {
+    pub fn push(&mut self, at: Instant, usage_bytes: u64) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((at, usage_bytes));
+    }
+
+    /// Least-squares slope over the window in bytes per second (negative when
+    /// shrinking); None until two readings at different times exist.
+    pub fn bytes_per_sec(&self) -> Option<f64> {
+        let (start, _) = *self.samples.front()?;
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .map(|&(at, usage)| (at.duration_since(start).as_secs_f64(), usage as f64))
+            .collect();
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let mean_u = points.iter().map(|(_, u)| u).sum::<f64>() / n;
+        let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+        if variance <= 0.0 {
+            return None;
+        }
+        let covariance: f64 = points.iter().map(|(t, u)| (t - mean_t) * (u - mean_u)).sum();
+        Some(covariance / variance)
+    }
+}
+
+/// Seconds until `usage` reaches `limit` at `bytes_per_sec`; None unless
+/// usage is growing and still below the limit.
+pub fn seconds_to_limit(bytes_per_sec: f64, usage: u64, limit: u64) -> Option<f64> {
+    (bytes_per_sec > 0.0 && usage < limit).then(|| (limit - usage) as f64 / bytes_per_sec)
+}
+
+/// Rough duration for status lines: "45s", "12m", "3h".
+pub fn format_eta(seconds: f64) -> String {
+    match seconds {
+        s if s < 90.0 => format!("{:.0}s", s),
+        s if s < 90.0 * 60.0 => format!("{:.0}m", s / 60.0),
+        s => format!("{:.0}h", s / 3600.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn growth_rate_fits_the_window_and_extrapolates() {
+        let start = Instant::now();
+        let mut trend = MemoryTrend::default();
+        trend.push(start, 100 << 20);
+        assert_eq!(trend.bytes_per_sec(), None);
+        for second in 1..=20u64 {
+            trend.push(start + Duration::from_secs(second), (100 + 4 * second) << 20);
+        }
+        let rate = trend.bytes_per_sec().unwrap();
+        assert!((rate - (4 << 20) as f64).abs() < 1.0, "{}", rate);
+
+        assert_eq!(seconds_to_limit(rate, 180 << 20, 660 << 20).map(format_eta), Some("2m".to_string()));
+        // Shrinking or already over the limit: no extrapolation
+        assert_eq!(seconds_to_limit(-rate, 180 << 20, 660 << 20), None);
+        assert_eq!(seconds_to_limit(rate, 700 << 20, 660 << 20), None);
+    }
+}
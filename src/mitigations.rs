@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+const SYS_CPU: &str = "/sys/devices/system/cpu";
+
+/// A CPU vulnerability whose mitigation changes usable capacity (today: the
+/// ones that disable SMT or leave it exposed).
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Mitigation {
+    pub name: String,
+    pub status: String,
+}
+
+/// Reads `/sys/devices/system/cpu/vulnerabilities/*`, keeping only entries
+/// that mention SMT being disabled or vulnerable.
+pub fn read_capacity_mitigations() -> Vec<Mitigation> {
+    let mut mitigations = Vec::new();
+    let Ok(entries) = fs::read_dir(Path::new(SYS_CPU).join("vulnerabilities")) else {
+        return mitigations;
+    };
+    for entry in entries.flatten() {
+        let Ok(status) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let status = status.trim();
+        if affects_capacity(status) {
+            mitigations.push(Mitigation {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                status: status.to_string(),
+            });
+        }
+    }
+    mitigations.sort_by(|a, b| a.name.cmp(&b.name));
+    mitigations
+}
+
+fn affects_capacity(status: &str) -> bool {
+    let lower = status.to_ascii_lowercase();
+    lower.contains("smt disabled") || lower.contains("smt vulnerable")
+}
+
+/// True when SMT has been switched off (by boot parameter or at runtime),
+/// as opposed to being unsupported by the CPU.
+pub fn smt_disabled() -> bool {
+    let control = fs::read_to_string(Path::new(SYS_CPU).join("smt/control")).unwrap_or_default();
+    matches!(control.trim(), "off" | "forceoff")
+}
+
+/// True when SMT is off and at least one mitigation says it turned SMT off.
+pub fn explains_smt_off(mitigations: &[Mitigation]) -> bool {
+    smt_disabled()
+        && mitigations
+            .iter()
+            .any(|m| m.status.to_ascii_lowercase().contains("smt disabled"))
+}
@@ -0,0 +1,91 @@
+use std::fs;
+
+/// One visible mount from `/proc/self/mountinfo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mount {
+    /// Field 4: the directory of the filesystem mounted here. For a cgroup
+    /// mount inside a container this is the container's own cgroup
+    pub root: String,
+    /// Field 5
+    pub mount_point: String,
+    pub fstype: String,
+    /// Comma-separated; for v1 cgroup mounts it names the controllers
+    pub super_options: String,
+}
+
+/// Mounts visible to this process; empty when mountinfo is unreadable.
+pub fn read_mounts() -> Vec<Mount> {
+    fs::read_to_string("/proc/self/mountinfo").map(|contents| parse_mountinfo(&contents)).unwrap_or_default()
+}
+
+/// Parses mountinfo lines. The filesystem type follows the "-" separator that
+/// ends the optional fields. A later mount over the same point hides the
+/// earlier ones, so only the last is kept.
+pub fn parse_mountinfo(contents: &str) -> Vec<Mount> {
+    let mounts: Vec<Mount> = contents
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let separator = fields.iter().position(|&field| field == "-")?;
+            Some(Mount {
+                root: unescape_octal(fields.get(3)?),
+                mount_point: unescape_octal(fields.get(4)?),
+                fstype: fields.get(separator + 1)?.to_string(),
+                super_options: fields.get(separator + 3).unwrap_or(&"").to_string(),
+            })
+        })
+        .collect();
+    mounts
+        .iter()
+        .enumerate()
+        .filter(|(index, mount)| !mounts[index + 1..].iter().any(|later| later.mount_point == mount.mount_point))
+        .map(|(_, mount)| mount.clone())
+        .collect()
+}
+
+/// mountinfo escapes space, tab, newline and backslash as `\ooo`.
+fn unescape_octal(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(index) = rest.find('\\') {
+        out.push_str(&rest[..index]);
+        let code = rest.get(index + 1..index + 4).and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[index + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mountinfo_types_follow_the_separator() {
+        let contents = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+24 22 0:22 / /run rw,nosuid - tmpfs tmpfs rw,mode=755
+25 22 8:2 / /scratch\\040space rw,relatime - xfs /dev/sdb1 rw
+26 24 0:23 / /run rw,nosuid - ext4 /dev/sdc1 rw
+31 25 0:26 /docker/abc /sys/fs/cgroup/memory ro,nosuid master:12 - cgroup cgroup rw,memory
+";
+        let mounts = parse_mountinfo(contents);
+        // Lines without optional fields, and escaped spaces
+        assert_eq!(mounts[1].mount_point, "/scratch space");
+        assert_eq!(mounts[1].fstype, "xfs");
+        // The ext4 mounted over /run hides the tmpfs beneath it
+        assert_eq!((mounts[2].mount_point.as_str(), mounts[2].fstype.as_str()), ("/run", "ext4"));
+        assert_eq!(mounts[3].root, "/docker/abc");
+        assert_eq!(mounts[3].super_options, "rw,memory");
+        assert_eq!(mounts.len(), 4);
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cgroupfs;
+use crate::cpulist;
+
+const SYS_NODE: &str = "/sys/devices/system/node";
+
+/// Memory nodes a cgroup may allocate from, and how much RAM they hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRestriction {
+    /// As the kernel lists them, e.g. "1" or "0-1"
+    pub allowed_nodes: String,
+    pub allowed_total_bytes: u64,
+}
+
+/// Reads `cpuset.mems.effective` (v2) or `cpuset.mems` (v1) and sums the
+/// allowed nodes' MemTotal. None on single-node machines, where the answer
+/// is always "all of it".
+pub fn read_node_restriction(cgroup_path: &str) -> Option<NodeRestriction> {
+    let online = fs::read_to_string(Path::new(SYS_NODE).join("online")).ok()?;
+    if cpulist::parse_cpu_list(&online).len() < 2 {
+        return None;
+    }
+    let allowed_nodes = read_cpuset_mems_list(cgroup_path)?;
+    let allowed_total_bytes = cpulist::parse_cpu_list(&allowed_nodes)
+        .into_iter()
+        .map(|node| {
+            let meminfo = Path::new(SYS_NODE).join(format!("node{}", node)).join("meminfo");
+            fs::read_to_string(meminfo).ok().and_then(|contents| parse_node_memtotal(&contents))
+        })
+        .sum::<Option<u64>>()?;
+    Some(NodeRestriction { allowed_nodes, allowed_total_bytes })
+}
+
+/// Memory nodes the cgroup is bound to (`cpuset.mems.effective` on v2,
+/// `cpuset.mems` on v1), on single-node machines too.
+pub fn read_cpuset_mems(cgroup_path: &str) -> Option<Vec<u32>> {
+    Some(cpulist::parse_cpu_list(&read_cpuset_mems_list(cgroup_path)?)).filter(|nodes| !nodes.is_empty())
+}
+
+fn read_cpuset_mems_list(cgroup_path: &str) -> Option<String> {
+    let mounts = cgroupfs::mounts();
+    [mounts.v2_file(cgroup_path, "cpuset.mems.effective"), mounts.v1_file("cpuset", cgroup_path, "cpuset.mems")]
+        .iter()
+        .flatten()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|list| list.trim().to_string())
+        .filter(|list| !list.is_empty())
+}
+
+/// NUMA nodes that hold any of `cpus`, from each node's `cpulist`.
+pub fn nodes_of_cpus(cpus: &[u32]) -> Vec<u32> {
+    nodes_of_cpus_in(Path::new(SYS_NODE), cpus)
+}
+
+fn nodes_of_cpus_in(root: &Path, cpus: &[u32]) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let node = entry.file_name().to_str()?.strip_prefix("node")?.parse::<u32>().ok()?;
+            let node_cpus = cpulist::parse_cpu_list(&fs::read_to_string(entry.path().join("cpulist")).ok()?);
+            node_cpus.iter().any(|cpu| cpus.contains(cpu)).then_some(node)
+        })
+        .collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+/// Warns when the cgroup's CPUs sit on NUMA nodes its memory may not come
+/// from, so every access is remote.
+pub fn binding_mismatch_warning(cpu_nodes: &[u32], memory_nodes: &[u32]) -> Option<String> {
+    if cpu_nodes.is_empty() || cpu_nodes.iter().any(|node| memory_nodes.contains(node)) {
+        return None;
+    }
+    Some(format!(
+        "CPUs are on NUMA node {} but memory is bound to node {} (cpuset.mems); every memory access crosses nodes",
+        cpulist::format_cpu_list(cpu_nodes),
+        cpulist::format_cpu_list(memory_nodes)
+    ))
+}
+
+/// A node's `meminfo` prefixes every line with the node: "Node 1 MemTotal:  65536 kB".
+fn parse_node_memtotal(contents: &str) -> Option<u64> {
+    contents.lines().find_map(|line| {
+        let (name, rest) = line.split_once(':')?;
+        if !name.ends_with(" MemTotal") {
+            return None;
+        }
+        Some(rest.split_whitespace().next()?.parse::<u64>().ok()? * 1024)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::fixture_dir;
+
+    #[test]
+    fn node_memtotal_skips_the_node_prefix() {
+        let contents = "Node 1 MemTotal:       65536 kB\nNode 1 MemFree:        1024 kB\n";
+        assert_eq!(parse_node_memtotal(contents), Some(65536 * 1024));
+        assert_eq!(parse_node_memtotal("Node 1 MemFree: 1024 kB\n"), None);
+    }
+
+    #[test]
+    fn cpus_and_memory_on_different_nodes_warn() {
+        let root = fixture_dir("numa", &[("node0/cpulist", "0-3\n"), ("node1/cpulist", "4-7\n"), ("online", "0-1\n")]);
+        assert_eq!(nodes_of_cpus_in(&root, &[2, 3]), vec![0]);
+        assert_eq!(nodes_of_cpus_in(&root, &[3, 4]), vec![0, 1]);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(binding_mismatch_warning(&[0], &[1]).unwrap().starts_with("CPUs are on NUMA node 0 but memory is bound to node 1"));
+        assert_eq!(binding_mismatch_warning(&[0, 1], &[1]), None);
+        assert_eq!(binding_mismatch_warning(&[], &[1]), None);
+    }
+}
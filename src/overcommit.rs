@@ -0,0 +1,99 @@
+use std::fs;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// `Committed_AS` above this fraction of `CommitLimit` under strict
+/// accounting gets a warning.
+pub const COMMIT_WARN_FRACTION: f64 = 0.90;
+
+/// The kernel's overcommit policy and commit charge. `commit_limit_bytes` is
+/// only enforced under policy 2 ("never").
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Overcommit {
+    /// "heuristic" (0), "always" (1) or "never" (2)
+    pub policy: String,
+    pub ratio_percent: Option<u64>,
+    /// `overcommit_kbytes` in bytes; when nonzero it replaces the ratio
+    pub fixed_limit_bytes: Option<u64>,
+    pub commit_limit_bytes: Option<u64>,
+    pub committed_as_bytes: Option<u64>,
+}
+
+impl Overcommit {
+    /// True when allocations are refused once `Committed_AS` reaches
+    /// `CommitLimit`, and it is nearly there.
+    pub fn near_commit_limit(&self) -> bool {
+        match (self.policy.as_str(), self.commit_limit_bytes, self.committed_as_bytes) {
+            ("never", Some(limit), Some(committed)) if limit > 0 => {
+                committed as f64 > limit as f64 * COMMIT_WARN_FRACTION
+            }
+            _ => false,
+        }
+    }
+}
+
+pub fn read_overcommit() -> Option<Overcommit> {
+    let read = |name: &str| {
+        fs::read_to_string(format!("/proc/sys/vm/{}", name))
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    let policy = policy_name(read("overcommit_memory")?)?;
+    let (commit_limit_bytes, committed_as_bytes) = fs::read_to_string("/proc/meminfo")
+        .map(|contents| parse_commit_charge(&contents))
+        .unwrap_or_default();
+    Some(Overcommit {
+        policy: policy.to_string(),
+        ratio_percent: read("overcommit_ratio"),
+        fixed_limit_bytes: read("overcommit_kbytes").map(|kb| kb * 1024),
+        commit_limit_bytes,
+        committed_as_bytes,
+    })
+}
+
+fn policy_name(value: u64) -> Option<&'static str> {
+    match value {
+        0 => Some("heuristic"),
+        1 => Some("always"),
+        2 => Some("never"),
+        _ => None,
+    }
+}
+
+/// (CommitLimit, Committed_AS) in bytes from `/proc/meminfo`.
+fn parse_commit_charge(contents: &str) -> (Option<u64>, Option<u64>) {
+    let value = |key: &str| {
+        contents.lines().find_map(|line| {
+            let (name, rest) = line.split_once(':')?;
+            if name.trim() != key {
+                return None;
+            }
+            Some(rest.split_whitespace().next()?.parse::<u64>().ok()? * 1024)
+        })
+    };
+    (value("CommitLimit"), value("Committed_AS"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_policy_near_limit_is_flagged() {
+        let (limit, committed) = parse_commit_charge("CommitLimit:     1000 kB\nCommitted_AS:     950 kB\n");
+        assert_eq!(limit, Some(1000 * 1024));
+        assert_eq!(committed, Some(950 * 1024));
+
+        let mut overcommit = Overcommit {
+            policy: "never".to_string(),
+            ratio_percent: Some(50),
+            fixed_limit_bytes: Some(0),
+            commit_limit_bytes: limit,
+            committed_as_bytes: committed,
+        };
+        assert!(overcommit.near_commit_limit());
+        overcommit.policy = "heuristic".to_string();
+        assert!(!overcommit.near_commit_limit());
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SEEN: Mutex<Option<HashSet<(String, i32)>>> = Mutex::new(None);
+
+/// Start logging probe failures to stderr (`--verbose` and `--strict`).
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// `fs::read_to_string` that, once enabled, reports a failure to stderr the
+/// first time each (path, errno) pair is seen, so `--watch` loops stay
+/// readable. Missing files are not reported: most probes try several
+/// locations (v2, then v1) and expect all but one to be absent.
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    let result = fs::read_to_string(path);
+    if let Err(err) = &result
+        && err.kind() != io::ErrorKind::NotFound
+        && ENABLED.load(Ordering::Relaxed)
+    {
+        let errno = err.raw_os_error().unwrap_or(0);
+        let mut seen = SEEN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if first_time(seen.get_or_insert_with(HashSet::new), path, errno) {
+            eprintln!("systemcheck: cannot read {} (errno {}): {}", path.display(), errno, err);
+        }
+    }
+    result
+}
+
+fn first_time(seen: &mut HashSet<(String, i32)>, path: &Path, errno: i32) -> bool {
+    seen.insert((path.display().to_string(), errno))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_reported_once_per_path_and_errno() {
+        let mut seen = HashSet::new();
+        let path = Path::new("/sys/fs/cgroup/memory.max");
+        assert!(first_time(&mut seen, path, libc::EACCES));
+        assert!(!first_time(&mut seen, path, libc::EACCES));
+        // A different errno on the same path is new information
+        assert!(first_time(&mut seen, path, libc::EIO));
+        assert!(first_time(&mut seen, Path::new("/proc/meminfo"), libc::EACCES));
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+
+use crate::{cgroupfs, probelog};
+
+/// Resident memory of one process in a cgroup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessMemory {
+    pub pid: u32,
+    /// `Name:` from /proc/<pid>/status (the command, truncated to 15 bytes)
+    pub name: String,
+    pub rss_bytes: u64,
+}
+
+/// PIDs listed in the cgroup's `cgroup.procs` (v2, then the v1 memory
+/// hierarchy).
+pub fn read_cgroup_pids(cgroup_path: &str) -> Option<Vec<u32>> {
+    let mounts = cgroupfs::mounts();
+    [mounts.v2_file(cgroup_path, "cgroup.procs"), mounts.v1_file("memory", cgroup_path, "cgroup.procs")]
+        .iter()
+        .flatten()
+        .find_map(|path| probelog::read_to_string(path).ok())
+        .map(|contents| contents.lines().filter_map(|line| line.trim().parse().ok()).collect())
+}
+
+/// The `count` processes with the largest RSS, largest first, and how many
+/// processes the cgroup holds. A process that exits between listing and
+/// reading its status is skipped, as are kernel threads (no `VmRSS`).
+pub fn top_consumers(cgroup_path: &str, count: usize) -> Option<(usize, Vec<ProcessMemory>)> {
+    let pids = read_cgroup_pids(cgroup_path)?;
+    let mut processes: Vec<ProcessMemory> = pids
+        .iter()
+        .filter_map(|&pid| {
+            let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+            let (name, rss_bytes) = parse_status(&status)?;
+            Some(ProcessMemory { pid, name, rss_bytes })
+        })
+        .collect();
+    processes.sort_by(|a, b| b.rss_bytes.cmp(&a.rss_bytes).then(a.pid.cmp(&b.pid)));
+    processes.truncate(count);
+    Some((pids.len(), processes))
+}
+
+/// `Name:` and `VmRSS:` (kB) from a /proc/<pid>/status file.
+fn parse_status(contents: &str) -> Option<(String, u64)> {
+    let mut name = None;
+    let mut rss = None;
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key {
+            "Name" => name = Some(value.trim().to_string()),
+            "VmRSS" => rss = value.split_whitespace().next().and_then(|kb| kb.parse::<u64>().ok()),
+            _ => {}
+        }
+    }
+    Some((name?, rss? * 1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_needs_name_and_rss() {
+        let status = "Name:\tpython3\nState:\tS (sleeping)\nVmRSS:\t  204800 kB\n";
+        assert_eq!(parse_status(status), Some(("python3".to_string(), 204800 * 1024)));
+        // Kernel threads have no VmRSS line
+        assert_eq!(parse_status("Name:\tkworker/0:1\nState:\tI (idle)\n"), None);
+    }
+}
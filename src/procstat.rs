@@ -0,0 +1,135 @@
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Cumulative CPU time counters (in USER_HZ ticks) from one `cpu` line of
+/// `/proc/stat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    pub fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq + self.steal
+    }
+}
+
+/// Named CPU time counters, as returned by [`parse_proc_stat`].
+pub type ProcStat = Vec<(String, CpuTimes)>;
+
+/// Parses every `cpu*` line of `/proc/stat`, returning ("cpu", aggregate)
+/// first followed by ("cpuN", ...) entries in file order.
+pub fn parse_proc_stat(contents: &str) -> ProcStat {
+    contents
+        .lines()
+        .filter(|line| line.starts_with("cpu"))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?.to_string();
+            let values: Vec<u64> = fields.filter_map(|f| f.parse::<u64>().ok()).collect();
+            if values.len() < 4 {
+                return None;
+            }
+            let get = |idx: usize| values.get(idx).copied().unwrap_or(0);
+            Some((
+                name,
+                CpuTimes {
+                    user: get(0),
+                    nice: get(1),
+                    system: get(2),
+                    idle: get(3),
+                    iowait: get(4),
+                    irq: get(5),
+                    softirq: get(6),
+                    steal: get(7),
+                },
+            ))
+        })
+        .collect()
+}
+
+pub fn read_proc_stat() -> ProcStat {
+    fs::read_to_string("/proc/stat")
+        .map(|contents| parse_proc_stat(&contents))
+        .unwrap_or_default()
+}
+
+/// Reads `/proc/stat` twice, `window` apart, returning (before, after).
+pub fn sample_proc_stat(window: Duration) -> Option<(ProcStat, ProcStat)> {
+    let before = read_proc_stat();
+    if before.is_empty() {
+        return None;
+    }
+    thread::sleep(window);
+    let after = read_proc_stat();
+    if after.is_empty() {
+        return None;
+    }
+    Some((before, after))
+}
+
+/// Percentage of all CPU time between two samples that the hypervisor stole.
+pub fn steal_percent(before: &CpuTimes, after: &CpuTimes) -> Option<f64> {
+    let total = after.total().checked_sub(before.total())?;
+    if total == 0 {
+        return None;
+    }
+    let steal = after.steal.saturating_sub(before.steal);
+    Some(steal as f64 / total as f64 * 100.0)
+}
+
+/// Percentage of time between two samples that a CPU was not idle (idle and
+/// iowait both count as idle).
+pub fn busy_percent(before: &CpuTimes, after: &CpuTimes) -> Option<f64> {
+    let total = after.total().checked_sub(before.total())?;
+    if total == 0 {
+        return None;
+    }
+    let idle = (after.idle + after.iowait).saturating_sub(before.idle + before.iowait);
+    Some(total.saturating_sub(idle) as f64 / total as f64 * 100.0)
+}
+
+/// Busy share of one logical CPU over a sampling window.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct PerCpuBusy {
+    pub cpu: u32,
+    pub busy_percent: f64,
+}
+
+/// Steal percentage of the aggregate `cpu` line between two samples.
+pub fn aggregate_steal_percent(before: &ProcStat, after: &ProcStat) -> Option<f64> {
+    let before = before.iter().find(|(name, _)| name == "cpu")?;
+    let after = after.iter().find(|(name, _)| name == "cpu")?;
+    steal_percent(&before.1, &after.1)
+}
+
+/// Busy percentage of each `cpuN` line present in both samples, limited to
+/// `only` when given (e.g. the cgroup's cpuset).
+pub fn per_cpu_busy(before: &ProcStat, after: &ProcStat, only: Option<&[u32]>) -> Vec<PerCpuBusy> {
+    after
+        .iter()
+        .filter_map(|(name, times)| {
+            let cpu = name.strip_prefix("cpu")?.parse::<u32>().ok()?;
+            if only.is_some_and(|ids| !ids.contains(&cpu)) {
+                return None;
+            }
+            let (_, previous) = before.iter().find(|(other, _)| other == name)?;
+            Some(PerCpuBusy {
+                cpu,
+                busy_percent: busy_percent(previous, times)?,
+            })
+        })
+        .collect()
+}
+
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The kind of environment we run in, which decides how alarming a missing
+/// limit is: expected on bare metal, suspicious in a container.
+#[derive(clap::ValueEnum, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Profile {
+    /// Docker, Podman, Kubernetes, LXC or systemd-nspawn
+    Container,
+    /// A physical host
+    BareMetal,
+    /// A virtual machine guest
+    Vm,
+}
+
+impl Profile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Container => "container",
+            Profile::BareMetal => "bare-metal",
+            Profile::Vm => "vm",
+        }
+    }
+}
+
+/// Runtime cgroup names that show up in PID 1's `/proc/1/cgroup` inside a
+/// container.
+const CONTAINER_CGROUP_MARKERS: &[&str] = &["/docker", "/kubepods", "/lxc", "/libpod-", "/containerd", "/crio-"];
+
+/// DMI vendors and products of common hypervisors.
+const VM_DMI_MARKERS: &[&str] = &["QEMU", "KVM", "VMware", "VirtualBox", "Xen", "Amazon EC2", "Google Compute Engine", "Virtual Machine"];
+
+/// A container when a runtime left its marker (`/.dockerenv`,
+/// `/run/.containerenv`, `container=` in PID 1's environment, a runtime
+/// cgroup); otherwise a VM when the CPU or firmware names a hypervisor;
+/// otherwise bare metal. A container inside a VM counts as a container.
+pub fn detect() -> Profile {
+    let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+    let marker_file = Path::new("/.dockerenv").exists() || Path::new("/run/.containerenv").exists();
+    let dmi = format!("{}\n{}", read("/sys/class/dmi/id/sys_vendor"), read("/sys/class/dmi/id/product_name"));
+    classify(
+        marker_file,
+        &read("/proc/1/environ"),
+        &read("/proc/1/cgroup"),
+        &read("/proc/cpuinfo"),
+        &dmi,
+    )
+}
+
+fn classify(marker_file: bool, pid1_environ: &str, pid1_cgroup: &str, cpuinfo: &str, dmi: &str) -> Profile {
+    let container = marker_file
+        || pid1_environ.split('\0').any(|var| var.starts_with("container="))
+        || pid1_cgroup.lines().any(|line| CONTAINER_CGROUP_MARKERS.iter().any(|marker| line.contains(marker)));
+    if container {
+        return Profile::Container;
+    }
+    let hypervisor_flag = cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+    if hypervisor_flag || VM_DMI_MARKERS.iter().any(|marker| dmi.contains(marker)) {
+        Profile::Vm
+    } else {
+        Profile::BareMetal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn containers_win_over_the_hypervisor_underneath() {
+        let vm_cpu = "processor\t: 0\nflags\t\t: fpu vme hypervisor lahf_lm\n";
+        assert_eq!(classify(false, "", "0::/\n", vm_cpu, ""), Profile::Vm);
+        assert_eq!(classify(true, "", "0::/\n", vm_cpu, ""), Profile::Container);
+        assert_eq!(classify(false, "PATH=/bin\0container=podman\0", "", "", ""), Profile::Container);
+        assert_eq!(classify(false, "", "12:memory:/kubepods/burstable/pod1/abc\n", "", ""), Profile::Container);
+        assert_eq!(classify(false, "", "", "flags\t\t: fpu vme\n", "QEMU\nStandard PC (Q35 + ICH9, 2009)\n"), Profile::Vm);
+        assert_eq!(classify(false, "", "0::/init.scope\n", "flags\t\t: fpu vme\n", "Dell Inc.\nPowerEdge R750\n"), Profile::BareMetal);
+    }
+}
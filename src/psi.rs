@@ -0,0 +1,128 @@
+use std::fs;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::cgroupfs;
+
+/// Pressure stall information for one resource, from a PSI file such as
+/// `/proc/pressure/memory` or a cgroup's `memory.pressure`. Averages are
+/// percentages of wall time; totals are cumulative microseconds.
+#[derive(Serialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct PressureStats {
+    /// "cgroup" or "system"
+    pub source: String,
+    pub some_avg10: Option<f64>,
+    pub some_avg60: Option<f64>,
+    pub some_avg300: Option<f64>,
+    pub some_total_usec: Option<u64>,
+    pub full_avg10: Option<f64>,
+    pub full_avg60: Option<f64>,
+    pub full_avg300: Option<f64>,
+    pub full_total_usec: Option<u64>,
+}
+
+/// Reads PSI for `resource` ("memory", "cpu", "io"), preferring the cgroup's
+/// own file and falling back to the system-wide one. None when the kernel has
+/// no PSI support.
+pub fn read_pressure(resource: &str, cgroup_path: &str) -> Option<PressureStats> {
+    let cgroup_file = cgroupfs::mounts().v2_file(cgroup_path, &format!("{}.pressure", resource));
+    if let Some(contents) = cgroup_file.and_then(|path| fs::read_to_string(path).ok()) {
+        return Some(parse_pressure(&contents, "cgroup"));
+    }
+    fs::read_to_string(format!("/proc/pressure/{}", resource))
+        .ok()
+        .map(|contents| parse_pressure(&contents, "system"))
+}
+
+impl PressureStats {
+    /// "some 1.50/0.75/0.10%, full 0.25/0.05/0.00% (avg10/60/300)"; the full
+    /// part is left out on kernels without a `full` line.
+    pub fn describe(&self) -> String {
+        let averages = |a: Option<f64>, b: Option<f64>, c: Option<f64>| match (a, b, c) {
+            (Some(a), Some(b), Some(c)) => Some(format!("{:.2}/{:.2}/{:.2}%", a, b, c)),
+            _ => None,
+        };
+        let mut parts = Vec::new();
+        if let Some(some) = averages(self.some_avg10, self.some_avg60, self.some_avg300) {
+            parts.push(format!("some {}", some));
+        }
+        if let Some(full) = averages(self.full_avg10, self.full_avg60, self.full_avg300) {
+            parts.push(format!("full {}", full));
+        }
+        format!("{} (avg10/60/300)", parts.join(", "))
+    }
+}
+
+/// Parses the two-line PSI format:
+/// `some avg10=0.00 avg60=0.00 avg300=0.00 total=0`
+pub fn parse_pressure(contents: &str, source: &str) -> PressureStats {
+    let mut stats = PressureStats {
+        source: source.to_string(),
+        ..PressureStats::default()
+    };
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+        let mut avg10 = None;
+        let mut avg60 = None;
+        let mut avg300 = None;
+        let mut total = None;
+        for field in fields {
+            match field.split_once('=') {
+                Some(("avg10", value)) => avg10 = value.parse().ok(),
+                Some(("avg60", value)) => avg60 = value.parse().ok(),
+                Some(("avg300", value)) => avg300 = value.parse().ok(),
+                Some(("total", value)) => total = value.parse().ok(),
+                _ => {}
+            }
+        }
+        match kind {
+            Some("some") => {
+                stats.some_avg10 = avg10;
+                stats.some_avg60 = avg60;
+                stats.some_avg300 = avg300;
+                stats.some_total_usec = total;
+            }
+            Some("full") => {
+                stats.full_avg10 = avg10;
+                stats.full_avg60 = avg60;
+                stats.full_avg300 = avg300;
+                stats.full_total_usec = total;
+            }
+            _ => {}
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pressure_reads_some_and_full() {
+        let contents = "\
+some avg10=1.50 avg60=0.75 avg300=0.10 total=123456
+full avg10=0.25 avg60=0.05 avg300=0.00 total=7890
+";
+        let stats = parse_pressure(contents, "cgroup");
+        assert_eq!(stats.source, "cgroup");
+        assert_eq!(stats.some_avg10, Some(1.5));
+        assert_eq!(stats.some_avg300, Some(0.1));
+        assert_eq!(stats.some_total_usec, Some(123456));
+        assert_eq!(stats.full_avg10, Some(0.25));
+        assert_eq!(stats.full_total_usec, Some(7890));
+        assert_eq!(stats.describe(), "some 1.50/0.75/0.10%, full 0.25/0.05/0.00% (avg10/60/300)");
+    }
+
+    #[test]
+    fn parse_pressure_without_full_line() {
+        // The system-wide cpu file had no "full" line before 5.13
+        let stats = parse_pressure("some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n", "system");
+        assert_eq!(stats.some_avg10, Some(0.0));
+        assert_eq!(stats.full_avg10, None);
+        assert_eq!(stats.full_total_usec, None);
+        assert_eq!(stats.describe(), "some 0.00/0.00/0.00% (avg10/60/300)");
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cgroupfs;
+
+/// cgroup v2 files read for the target cgroup.
+const V2_FILES: &[&str] = &[
+    "cgroup.type",
+    "cgroup.controllers",
+    "cgroup.subtree_control",
+    "cpu.max",
+    "cpu.max.burst",
+    "cpu.idle",
+    "cpu.stat",
+    "cpu.pressure",
+    "cpuset.cpus.effective",
+    "memory.max",
+    "memory.high",
+    "memory.low",
+    "memory.min",
+    "memory.current",
+    "memory.swap.max",
+    "memory.swap.current",
+    "memory.stat",
+    "memory.events",
+    "memory.events.local",
+    "memory.oom.group",
+    "memory.pressure",
+];
+
+/// cgroup v1 (controller, file) pairs read for the target cgroup.
+const V1_FILES: &[(&str, &str)] = &[
+    ("cpu", "cpu.cfs_quota_us"),
+    ("cpu", "cpu.cfs_period_us"),
+    ("cpu", "cpu.stat"),
+    ("cpuacct", "cpuacct.usage"),
+    ("cpuacct", "cpuacct.usage_user"),
+    ("cpuacct", "cpuacct.usage_sys"),
+    ("cpuset", "cpuset.effective_cpus"),
+    ("cpuset", "cpuset.cpus"),
+    ("memory", "memory.limit_in_bytes"),
+    ("memory", "memory.usage_in_bytes"),
+    ("memory", "memory.memsw.limit_in_bytes"),
+    ("memory", "memory.memsw.usage_in_bytes"),
+    ("memory", "memory.kmem.usage_in_bytes"),
+    ("memory", "memory.stat"),
+    ("memory", "memory.oom_control"),
+    ("memory", "memory.swappiness"),
+];
+
+/// Every cgroup file systemcheck consults for `cgroup_path`, each under a
+/// `==> path <==` header. Missing files are skipped; unreadable ones are
+/// marked. Process lists (`cgroup.procs`, `tasks`) are left out so no PIDs
+/// end up in bug reports.
+pub fn dump(cgroup_path: &str) -> String {
+    let mounts = cgroupfs::mounts();
+    let mut paths = vec!["/proc/self/cgroup".to_string()];
+    let levels: &[&str] = if cgroup_path.is_empty() || cgroup_path == "/" { &[""] } else { &[cgroup_path, ""] };
+    for level in levels {
+        // The root is only consulted as a fallback for quotas and limits
+        let root = level.is_empty();
+        for file in V2_FILES {
+            if !root || matches!(*file, "cpu.max" | "memory.max" | "memory.current" | "cpuset.cpus.effective") {
+                paths.extend(mounts.v2_file(level, file));
+            }
+        }
+        if !root {
+            paths.extend(mounts.v2_dir(level).map(|dir| hugetlb_files(&dir)).unwrap_or_default());
+            paths.extend(mounts.v1_dir("hugetlb", level).map(|dir| hugetlb_files(&dir)).unwrap_or_default());
+        }
+        for (controller, file) in V1_FILES {
+            if root && matches!(*file, "cpu.cfs_quota_us" | "cpu.cfs_period_us" | "memory.limit_in_bytes" | "cpuset.cpus") {
+                paths.extend(mounts.v1_root_file(controller, file));
+            } else if !root {
+                paths.extend(mounts.v1_file(controller, level, file));
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for path in paths {
+        let body = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => "[permission denied]\n".to_string(),
+            Err(err) => format!("[error: {}]\n", err),
+        };
+        out.push_str(&format!("==> {} <==\n{}", path, body));
+        if !body.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// `hugetlb.<size>.*` files in a cgroup directory, sorted.
+fn hugetlb_files(dir: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.starts_with("hugetlb.").then(|| Path::new(dir).join(name).to_string_lossy().into_owned())
+        })
+        .collect();
+    files.sort();
+    files
+}
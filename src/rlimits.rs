@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Soft and hard limit for one resource; None means unlimited
+/// (`RLIM_INFINITY`). Byte-valued for as/data/memlock/stack/core, counts for
+/// nofile/nproc.
+#[derive(Serialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub struct Rlimit {
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+const RESOURCES: &[(&str, libc::__rlimit_resource_t)] = &[
+    ("as", libc::RLIMIT_AS),
+    ("data", libc::RLIMIT_DATA),
+    ("memlock", libc::RLIMIT_MEMLOCK),
+    ("stack", libc::RLIMIT_STACK),
+    ("nofile", libc::RLIMIT_NOFILE),
+    ("nproc", libc::RLIMIT_NPROC),
+    ("core", libc::RLIMIT_CORE),
+];
+
+/// Resource limits of this process, keyed by lowercase name ("as", "data", ...).
+pub fn read_rlimits() -> BTreeMap<String, Rlimit> {
+    RESOURCES
+        .iter()
+        .filter_map(|&(name, resource)| {
+            let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+            if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+                return None;
+            }
+            let finite = |value: libc::rlim_t| (value != libc::RLIM_INFINITY).then_some(value);
+            Some((name.to_string(), Rlimit { soft: finite(limit.rlim_cur), hard: finite(limit.rlim_max) }))
+        })
+        .collect()
+}
+
+/// True for the resources whose values are bytes rather than counts.
+pub fn is_byte_valued(name: &str) -> bool {
+    matches!(name, "as" | "data" | "memlock" | "stack" | "core")
+}
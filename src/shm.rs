@@ -0,0 +1,78 @@
+use std::ffi::CString;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+pub const DEFAULT_SHM_PATH: &str = "/dev/shm";
+
+/// Docker's default `--shm-size`.
+const DOCKER_DEFAULT_SHM_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Size and usage of the POSIX shared-memory mount (normally `/dev/shm`).
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct ShmInfo {
+    pub path: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub free_bytes: u64,
+}
+
+/// statfs on `path`; None when it does not exist, as in some minimal
+/// containers.
+pub fn read_shm(path: &str) -> Option<ShmInfo> {
+    let c_path = CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    let total_bytes = (stat.f_blocks as u64).saturating_mul(block);
+    let free_bytes = (stat.f_bavail as u64).saturating_mul(block);
+    Some(ShmInfo {
+        path: path.to_string(),
+        total_bytes,
+        used_bytes: total_bytes.saturating_sub((stat.f_bfree as u64).saturating_mul(block)),
+        free_bytes,
+    })
+}
+
+/// Warns about Docker's 64 MiB default when several CPUs could run workers
+/// that share memory through it (Python multiprocessing, MPI, PyTorch
+/// DataLoader), and notes when the cgroup limit is the real cap because
+/// `/dev/shm` pages are charged to the cgroup.
+pub fn shm_warnings(shm: &ShmInfo, available_cpus: usize, memory_limit: Option<u64>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if shm.total_bytes == DOCKER_DEFAULT_SHM_BYTES && available_cpus > 1 {
+        warnings.push(format!(
+            "small {} (64 MiB) — set --shm-size; multiprocessing and MPI workers can fail with bus errors",
+            shm.path
+        ));
+    }
+    if let Some(limit) = memory_limit
+        && shm.total_bytes > limit
+    {
+        warnings.push(format!(
+            "{} is larger than the cgroup memory limit; files written there count against the limit and can trigger the OOM killer",
+            shm.path
+        ));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shm(total_bytes: u64) -> ShmInfo {
+        ShmInfo { path: DEFAULT_SHM_PATH.to_string(), total_bytes, used_bytes: 0, free_bytes: total_bytes }
+    }
+
+    #[test]
+    fn docker_default_warns_only_with_multiple_cpus() {
+        assert_eq!(shm_warnings(&shm(DOCKER_DEFAULT_SHM_BYTES), 1, None), Vec::<String>::new());
+        let warnings = shm_warnings(&shm(DOCKER_DEFAULT_SHM_BYTES), 4, None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("small /dev/shm (64 MiB)"));
+        assert_eq!(shm_warnings(&shm(8 << 30), 4, Some(4 << 30)).len(), 1);
+    }
+}
@@ -0,0 +1,17 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// Creates a scratch directory tree from (relative path, contents) pairs at
+/// `<temp dir>/systemcheck-<name>-<pid>`, replacing any leftover from an
+/// earlier run. Tests remove it when done.
+pub fn fixture_dir<P: AsRef<str>, C: AsRef<str>>(name: &str, files: &[(P, C)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("systemcheck-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, contents) in files {
+        let path = root.join(path.as_ref());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents.as_ref()).unwrap();
+    }
+    fs::create_dir_all(&root).unwrap();
+    root
+}
@@ -0,0 +1,85 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::mountinfo::{self, Mount};
+use crate::shm;
+
+/// A tmpfs mount. Files on tmpfs live in RAM (or swap) and are charged to
+/// the writer's cgroup, so a large temp file can OOM a job.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct TmpfsMount {
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    /// This mount holds `$TMPDIR` (or /tmp when unset)
+    pub is_temp_dir: bool,
+}
+
+/// `$TMPDIR`, falling back to /tmp, with symlinks resolved.
+pub fn temp_dir() -> PathBuf {
+    let dir = env::var_os("TMPDIR").filter(|dir| !dir.is_empty()).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/tmp"));
+    fs::canonicalize(&dir).unwrap_or(dir)
+}
+
+/// Every tmpfs mount visible to this process, from `/proc/self/mountinfo`.
+pub fn read_tmpfs_mounts() -> Vec<TmpfsMount> {
+    let mounts = mountinfo::read_mounts();
+    let temp_mount = mount_containing(&mounts, &temp_dir()).map(|mount| mount.mount_point.clone());
+    mounts
+        .iter()
+        .filter(|mount| mount.fstype == "tmpfs")
+        .filter_map(|mount| {
+            let usage = shm::read_shm(&mount.mount_point)?;
+            Some(TmpfsMount {
+                mount_point: mount.mount_point.clone(),
+                total_bytes: usage.total_bytes,
+                used_bytes: usage.used_bytes,
+                is_temp_dir: temp_mount.as_ref() == Some(&mount.mount_point),
+            })
+        })
+        .collect()
+}
+
+/// Warns when the temp directory is on tmpfs and a cgroup memory limit
+/// applies: temp files then compete with the job's own memory.
+pub fn tmpfs_warning(mounts: &[TmpfsMount], memory_limit: Option<u64>) -> Option<String> {
+    memory_limit?;
+    let mount = mounts.iter().find(|mount| mount.is_temp_dir)?;
+    Some(format!(
+        "temp directory {} is on tmpfs ({}); files written there are held in RAM and count against the cgroup memory limit",
+        temp_dir().display(),
+        mount.mount_point
+    ))
+}
+
+/// The innermost mount whose mount point is `path` or one of its parents.
+fn mount_containing<'a>(mounts: &'a [Mount], path: &Path) -> Option<&'a Mount> {
+    mounts
+        .iter()
+        .filter(|mount| path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| Path::new(&mount.mount_point).components().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+23 22 0:21 / /tmp rw,nosuid,nodev shared:2 - tmpfs tmpfs rw,size=8388608k
+";
+
+    #[test]
+    fn temp_dir_resolves_to_the_innermost_mount() {
+        let mounts = mountinfo::parse_mountinfo(MOUNTINFO);
+        let mount = |path: &str| mount_containing(&mounts, Path::new(path)).map(|mount| (mount.mount_point.as_str(), mount.fstype.as_str()));
+        assert_eq!(mount("/tmp"), Some(("/tmp", "tmpfs")));
+        assert_eq!(mount("/tmp/job-1"), Some(("/tmp", "tmpfs")));
+        // "/tmpdata" is not under "/tmp"
+        assert_eq!(mount("/tmpdata"), Some(("/", "ext4")));
+    }
+}
@@ -17,18 +17,25 @@ struct SimpleCpuSummary {
     available_cpus: usize,
     system_logical_cpus: usize,
     constrained: bool,
+    sub_core_quota: bool,
+    constraint_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct SimpleMemorySummary {
     system_available_bytes: u64,
     cgroup_memory_limit_bytes: Option<u64>,
+    effective_available_bytes: u64,
+    cgroup_memory_usage_bytes: Option<u64>,
+    usage_percent_of_limit: Option<f64>,
     constrained: bool,
+    memory_pressure_warning: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct SimpleReport {
     version: String,
+    timestamp: String,
     cpu: SimpleCpuSummary,
     memory: SimpleMemorySummary,
 }
@@ -48,6 +55,7 @@ struct DetailedMemoryInfo {
     system_used_bytes: u64,
     cgroup_memory_limit_bytes: Option<u64>,
     cgroup_memory_usage_bytes: Option<u64>,
+    effective_available_bytes: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -56,6 +64,8 @@ struct DetailedCGroupInfo {
     current_path: String,
     cpu_quota: Option<f64>,
     memory_limit_bytes: Option<u64>,
+    effective_memory_limit_bytes: Option<u64>,
+    limiting_cgroup_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -83,6 +93,8 @@ struct SystemdCase {
     name: &'static str,
     cpu_quota_property: Option<&'static str>,
     memory_max_property: Option<&'static str>,
+    /// MemoryMax set on a dedicated parent slice rather than the unit itself
+    slice_memory_max_property: Option<&'static str>,
     expected_cpu: ExpectedCpuQuota,
     expected_memory: ExpectedMemoryLimit,
 }
@@ -102,11 +114,9 @@ fn systemd_run_available() -> bool {
     }
 }
 
+/// `--format json` stdout must be exactly one JSON document, so parse all of it.
 fn parse_detailed_report(bytes: &[u8]) -> Option<DetailedReport> {
-    let text = std::str::from_utf8(bytes).ok()?;
-    let start = text.find('{')?;
-    let end = text.rfind('}')?;
-    serde_json::from_str(&text[start..=end]).ok()
+    serde_json::from_slice(bytes).ok()
 }
 
 fn find_systemcheck_binary() -> Option<PathBuf> {
@@ -145,11 +155,11 @@ fn find_systemcheck_binary() -> Option<PathBuf> {
 
 fn run_simple_report(binary: &Path) -> Result<SimpleReport, Box<dyn std::error::Error>> {
     let output = Command::new(binary)
-        .arg("--json")
+        .args(["--format", "json"])
         .output()?;
     if !output.status.success() {
         return Err(format!(
-            "systemcheck --json exited with {:?}: {}{}",
+            "systemcheck --format json exited with {:?}: {}{}",
             output.status.code(),
             String::from_utf8_lossy(&output.stdout),
             String::from_utf8_lossy(&output.stderr)
@@ -164,11 +174,11 @@ fn run_simple_report(binary: &Path) -> Result<SimpleReport, Box<dyn std::error::
 fn run_detailed_report_direct(binary: &Path) -> Result<DetailedReport, Box<dyn std::error::Error>> {
     let output = Command::new(binary)
         .arg("-v")
-        .arg("--json")
+        .args(["--format", "json"])
         .output()?;
     if !output.status.success() {
         return Err(format!(
-            "systemcheck -v --json exited with {:?}: {}{}",
+            "systemcheck -v --format json exited with {:?}: {}{}",
             output.status.code(),
             String::from_utf8_lossy(&output.stdout),
             String::from_utf8_lossy(&output.stderr)
@@ -179,16 +189,14 @@ fn run_detailed_report_direct(binary: &Path) -> Result<DetailedReport, Box<dyn s
         .ok_or_else(|| "failed to parse JSON output from systemcheck".into())
 }
 
-fn run_case_via_systemd(binary: &Path, case: &SystemdCase)
-    -> Result<DetailedReport, Box<dyn std::error::Error>>
-{
+fn systemd_run_command(case: &SystemdCase, suffix: &str) -> Command {
     let mut cmd = Command::new("systemd-run");
     cmd.arg("--user")
         .arg("--wait")
         .arg("--collect")
         .arg("--pipe")
         .arg("--quiet")
-        .arg(format!("--unit=systemcheck-{}-{}", case.name, std::process::id()));
+        .arg(format!("--unit=systemcheck-{}{}-{}", case.name, suffix, std::process::id()));
 
     if let Some(quota) = case.cpu_quota_property {
         cmd.arg(format!("--property=CPUQuota={}", quota));
@@ -196,12 +204,55 @@ fn run_case_via_systemd(binary: &Path, case: &SystemdCase)
     if let Some(limit) = case.memory_max_property {
         cmd.arg(format!("--property=MemoryMax={}", limit));
     }
+    if case.slice_memory_max_property.is_some() {
+        cmd.arg(format!("--slice={}", slice_name(case)));
+    }
+    cmd
+}
+
+fn slice_name(case: &SystemdCase) -> String {
+    format!("systemcheck_{}_{}.slice", case.name, std::process::id())
+}
 
+/// Sets the case's slice properties before anything runs in it; the runtime
+/// drop-in is removed again by `revert_slice`.
+fn configure_slice(case: &SystemdCase) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(limit) = case.slice_memory_max_property else {
+        return Ok(());
+    };
+    let output = Command::new("systemctl")
+        .args(["--user", "set-property", "--runtime"])
+        .arg(slice_name(case))
+        .arg(format!("MemoryMax={}", limit))
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "systemctl set-property for case '{}' failed: {}",
+            case.name,
+            String::from_utf8_lossy(&output.stderr)
+        ).into());
+    }
+    Ok(())
+}
+
+fn revert_slice(case: &SystemdCase) {
+    if case.slice_memory_max_property.is_some() {
+        let _ = Command::new("systemctl").args(["--user", "revert"]).arg(slice_name(case)).output();
+    }
+}
+
+fn run_case_via_systemd(binary: &Path, case: &SystemdCase)
+    -> Result<DetailedReport, Box<dyn std::error::Error>>
+{
+    configure_slice(case)?;
+    let mut cmd = systemd_run_command(case, "");
     cmd.arg(binary)
         .arg("-v")
-        .arg("--json");
+        .args(["--format", "json"]);
 
-    let output = cmd.output()?;
+    let output = cmd.output();
+    revert_slice(case);
+    let output = output?;
     if !output.status.success() {
         return Err(format!(
             "systemd-run for case '{}' failed (status {:?}): {}{}",
@@ -216,6 +267,27 @@ fn run_case_via_systemd(binary: &Path, case: &SystemdCase)
         .ok_or_else(|| format!("failed to parse JSON output for case '{}'", case.name).into())
 }
 
+fn run_simple_case_via_systemd(binary: &Path, case: &SystemdCase)
+    -> Result<SimpleReport, Box<dyn std::error::Error>>
+{
+    let output = systemd_run_command(case, "-simple")
+        .arg(binary)
+        .args(["--format", "json"])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "systemd-run for simple case '{}' failed (status {:?}): {}{}",
+            case.name,
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ).into());
+    }
+
+    let text = std::str::from_utf8(&output.stdout)?.trim();
+    Ok(serde_json::from_str(text)?)
+}
+
 fn approx_eq(actual: f64, expected: f64, tolerance: f64) -> bool {
     (actual - expected).abs() <= tolerance
 }
@@ -251,11 +323,29 @@ fn simple_json_includes_version() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     assert_eq!(report.version, EXPECTED_VERSION);
+    // ISO-8601 UTC, e.g. 2025-01-31T14:05:09Z
+    assert_eq!(report.timestamp.len(), 20, "unexpected timestamp {}", report.timestamp);
+    assert!(report.timestamp.ends_with('Z'));
     assert!(report.cpu.system_logical_cpus > 0);
     assert!(report.cpu.available_cpus > 0);
-    let _ = report.cpu.constrained;
+    assert_eq!(
+        report.cpu.constrained,
+        report.cpu.constraint_reason.is_some(),
+        "constrained must come with a constraint reason"
+    );
+    if report.cpu.sub_core_quota {
+        assert!(report.cpu.constrained, "sub-core quota must mark CPU as constrained");
+    }
     assert!(report.memory.system_available_bytes > 0);
-    let _ = report.memory.cgroup_memory_limit_bytes;
+    assert!(report.memory.effective_available_bytes <= report.memory.system_available_bytes);
+    match (report.memory.cgroup_memory_limit_bytes, report.memory.usage_percent_of_limit) {
+        (Some(_), Some(percent)) => assert!((0.0..=100.0).contains(&percent), "usage percent {} out of range", percent),
+        (None, percent) => assert_eq!(percent, None, "usage percent without a limit"),
+        (Some(_), None) => assert_eq!(report.memory.cgroup_memory_usage_bytes, None),
+    }
+    if report.memory.memory_pressure_warning {
+        assert!(report.memory.cgroup_memory_limit_bytes.is_some(), "memory pressure warning without a limit");
+    }
     let _ = report.memory.constrained;
     Ok(())
 }
@@ -287,6 +377,7 @@ fn systemd_run_limits_reflected_in_json() -> Result<(), Box<dyn std::error::Erro
             name: "no_constraints",
             cpu_quota_property: None,
             memory_max_property: None,
+            slice_memory_max_property: None,
             expected_cpu: ExpectedCpuQuota::Baseline,
             expected_memory: ExpectedMemoryLimit::Baseline,
         },
@@ -294,6 +385,7 @@ fn systemd_run_limits_reflected_in_json() -> Result<(), Box<dyn std::error::Erro
             name: "memory_only",
             cpu_quota_property: None,
             memory_max_property: Some("256M"),
+            slice_memory_max_property: None,
             expected_cpu: ExpectedCpuQuota::Baseline,
             expected_memory: ExpectedMemoryLimit::Approx(mib(256)),
         },
@@ -301,16 +393,34 @@ fn systemd_run_limits_reflected_in_json() -> Result<(), Box<dyn std::error::Erro
             name: "cpu_only",
             cpu_quota_property: Some("150%"),
             memory_max_property: None,
+            slice_memory_max_property: None,
             expected_cpu: ExpectedCpuQuota::Approx(1.5),
             expected_memory: ExpectedMemoryLimit::Baseline,
         },
+        SystemdCase {
+            name: "sub_core_quota",
+            cpu_quota_property: Some("50%"),
+            memory_max_property: None,
+            slice_memory_max_property: None,
+            expected_cpu: ExpectedCpuQuota::Approx(0.5),
+            expected_memory: ExpectedMemoryLimit::Baseline,
+        },
         SystemdCase {
             name: "cpu_and_memory",
             cpu_quota_property: Some(CPU_QUOTA_PERCENT),
             memory_max_property: Some(MEMORY_LIMIT),
+            slice_memory_max_property: None,
             expected_cpu: ExpectedCpuQuota::Approx(2.0),
             expected_memory: ExpectedMemoryLimit::Approx(EXPECTED_MEMORY_BYTES),
         },
+        SystemdCase {
+            name: "parent_slice_memory",
+            cpu_quota_property: None,
+            memory_max_property: None,
+            slice_memory_max_property: Some("384M"),
+            expected_cpu: ExpectedCpuQuota::Baseline,
+            expected_memory: ExpectedMemoryLimit::Baseline,
+        },
     ];
 
     for case in cases.iter() {
@@ -385,7 +495,7 @@ fn systemd_run_limits_reflected_in_json() -> Result<(), Box<dyn std::error::Erro
             report.cgroup.current_path
         );
         if let Some(version) = &report.cgroup.version {
-            assert!(version == "v1" || version == "v2", "case '{}': unexpected cgroup version {}", case.name, version);
+            assert!(["v1", "v2", "hybrid"].contains(&version.as_str()), "case '{}': unexpected cgroup version {}", case.name, version);
         }
         match (&report.cpu.cgroup_cpu_quota, &report.cgroup.cpu_quota) {
             (Some(cpu_section), Some(cgroup_section)) => {
@@ -428,6 +538,41 @@ fn systemd_run_limits_reflected_in_json() -> Result<(), Box<dyn std::error::Erro
             }
         }
 
+        if let Some(effective) = report.cgroup.effective_memory_limit_bytes {
+            if let Some(configured) = report.cgroup.memory_limit_bytes {
+                assert!(
+                    effective <= configured,
+                    "case '{}': effective memory limit {} exceeds the configured {}",
+                    case.name,
+                    effective,
+                    configured
+                );
+            }
+            assert!(
+                report.cgroup.limiting_cgroup_path.is_some(),
+                "case '{}': effective memory limit without a limiting cgroup",
+                case.name
+            );
+        }
+        // The unit itself has no MemoryMax, so only the hierarchy walk finds it
+        if case.slice_memory_max_property.is_some() {
+            let effective = report.cgroup.effective_memory_limit_bytes;
+            assert!(
+                effective.is_some_and(|bytes| approx_eq_u64(bytes, mib(384), MEMORY_TOLERANCE_BYTES)),
+                "case '{}': expected effective memory limit ≈ {} but got {:?}",
+                case.name,
+                mib(384),
+                effective
+            );
+            let limiting = report.cgroup.limiting_cgroup_path.as_deref().unwrap_or_default();
+            assert!(
+                limiting.ends_with(&format!("/{}", slice_name(case))),
+                "case '{}': limit attributed to {:?}, not the parent slice",
+                case.name,
+                limiting
+            );
+        }
+
         match (&case.expected_cpu, baseline.cgroup.cpu_quota, report.cgroup.cpu_quota) {
             (ExpectedCpuQuota::Approx(expected), _, Some(actual)) => {
                 assert!(
@@ -469,6 +614,31 @@ fn systemd_run_limits_reflected_in_json() -> Result<(), Box<dyn std::error::Erro
             }
         }
 
+        // The simple report must flag a quota below the logical CPU count even
+        // when available_cpus still shows every CPU.
+        if let ExpectedCpuQuota::Approx(expected) = case.expected_cpu
+            && (expected.floor() as usize) < report.cpu.system_logical_cpus
+        {
+            match run_simple_case_via_systemd(&binary, case) {
+                Ok(simple) => {
+                    assert!(
+                        simple.cpu.constrained,
+                        "case '{}': quota {} on {} CPUs not reported as constrained",
+                        case.name,
+                        expected,
+                        simple.cpu.system_logical_cpus
+                    );
+                    assert_eq!(
+                        simple.cpu.constraint_reason.as_deref(),
+                        Some("cpu.max quota"),
+                        "case '{}': unexpected constraint reason",
+                        case.name
+                    );
+                }
+                Err(err) => eprintln!("skipping simple check for case '{}': {}", case.name, err),
+            }
+        }
+
         match (&case.expected_memory, baseline.memory.cgroup_memory_limit_bytes, report.memory.cgroup_memory_limit_bytes) {
             (ExpectedMemoryLimit::Approx(expected), _, Some(actual)) => {
                 assert!(
@@ -478,6 +648,14 @@ fn systemd_run_limits_reflected_in_json() -> Result<(), Box<dyn std::error::Erro
                     expected,
                     actual
                 );
+                // Something is always resident, so less than the whole limit is left
+                assert!(
+                    report.memory.effective_available_bytes < *expected,
+                    "case '{}': effective available {} is not below the {} limit",
+                    case.name,
+                    report.memory.effective_available_bytes,
+                    expected
+                );
             }
             (ExpectedMemoryLimit::Approx(_), _, None) => {
                 panic!("case '{}': expected memory limit value but got None", case.name);